@@ -0,0 +1,143 @@
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::Error;
+
+/// The letters words are made of, in alphabetical order. Letters may be any character, and the
+/// alphabet may have up to [`Alphabet::MAX_LEN`] letters.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    letters: Vec<char>,
+    /// Index of every ASCII letter, or `u8::MAX` if it is not in the alphabet.
+    ascii: [u8; 128],
+}
+
+impl Alphabet {
+    pub const MAX_LEN: usize = 128;
+
+    /// Creates an alphabet from its letters in alphabetical order, e.g. `"abcdefghijklmnopqrstuvwxyzæøå"`.
+    /// Letters are case-folded.
+    pub fn new(letters: &str) -> Result<Self, Error> {
+        let letters: Vec<char> = letters.nfc().flat_map(char::to_lowercase).collect();
+        if letters.is_empty() || letters.len() > Self::MAX_LEN {
+            return Err(Error::InvalidAlphabet(format!(
+                "must have between 1 and {} letters",
+                Self::MAX_LEN
+            )));
+        }
+        if let Some(c) = letters.iter().find(|c| c.is_whitespace()) {
+            return Err(Error::InvalidAlphabet(format!("{c:?} is whitespace")));
+        }
+        for (i, c) in letters.iter().enumerate() {
+            if letters[..i].contains(c) {
+                return Err(Error::InvalidAlphabet(format!("{c:?} appears twice")));
+            }
+        }
+        Ok(Self::from_letters(letters))
+    }
+
+    fn from_letters(letters: Vec<char>) -> Self {
+        let mut ascii = [u8::MAX; 128];
+        for (index, &letter) in letters.iter().enumerate() {
+            if letter.is_ascii() {
+                ascii[letter as usize] = index as u8;
+            }
+        }
+        Alphabet { letters, ascii }
+    }
+
+    /// The 26 letters from a to z.
+    pub fn english() -> Self {
+        Self::from_letters(('a'..='z').collect())
+    }
+
+    /// A built-in alphabet: english, danish, norwegian, swedish, german or spanish.
+    pub fn by_name(name: &str) -> Option<Self> {
+        let extra = match name {
+            "english" => "",
+            "danish" | "norwegian" => "æøå",
+            "swedish" => "åäö",
+            "german" => "äöüß",
+            "spanish" => "ñ",
+            _ => return None,
+        };
+        Some(Self::from_letters(
+            ('a'..='z').chain(extra.chars()).collect(),
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    pub fn letters(&self) -> &[char] {
+        &self.letters
+    }
+
+    pub fn contains(&self, letter: char) -> bool {
+        self.letters.contains(&letter)
+    }
+
+    pub(crate) fn index_of(&self, letter: char) -> Option<usize> {
+        match letter.is_ascii() {
+            true => Some(self.ascii[letter as usize])
+                .filter(|&i| i != u8::MAX)
+                .map(usize::from),
+            false => self.letters.iter().position(|&c| c == letter),
+        }
+    }
+
+    pub(crate) fn letter(&self, index: usize) -> char {
+        self.letters[index]
+    }
+
+    /// The bitword of `letters`, with bit i set for the i-th letter of the alphabet.
+    pub(crate) fn bitword(&self, letters: &str) -> Result<u128, Error> {
+        letters.chars().try_fold(0, |bitword, letter| {
+            let index = self.index_of(letter).ok_or(Error::InvalidLetter(letter))?;
+            Ok(bitword | 1 << index)
+        })
+    }
+
+    /// The letters of a bitword, in alphabetical order.
+    pub(crate) fn letters_of(&self, bitword: u128) -> impl Iterator<Item = char> + '_ {
+        (0..self.len())
+            .filter(move |index| bitword & (1 << index) != 0)
+            .map(|index| self.letter(index))
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+/// Parses the name of a built-in alphabet, or else the letters of an alphabet.
+impl FromStr for Alphabet {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::by_name(s).map_or_else(|| Self::new(s), Ok)
+    }
+}
+
+impl Debug for Alphabet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Alphabet({self:?})", self = self.to_string())
+    }
+}
+
+impl Display for Alphabet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.letters.iter().try_for_each(|c| write!(f, "{c}"))
+    }
+}
@@ -0,0 +1,43 @@
+use std::ops::{BitAnd, BitOr, Not};
+
+/// An unsigned integer with one bit per letter of the alphabet. The solver picks the narrowest
+/// type that fits the alphabet, so the common case of 26 letters stays in a `u32`.
+pub(crate) trait Bitword:
+    Copy + Eq + Send + Sync + BitAnd<Output = Self> + BitOr<Output = Self> + Not<Output = Self>
+{
+    const BITS: usize;
+    const ZERO: Self;
+
+    fn bit(letter: usize) -> Self;
+
+    /// The number of letters set.
+    fn count(self) -> usize;
+
+    fn is_set(self, letter: usize) -> bool {
+        self & Self::bit(letter) != Self::ZERO
+    }
+
+    /// The letters in `letters`.
+    fn mask(letters: std::ops::Range<usize>) -> Self {
+        letters.fold(Self::ZERO, |mask, letter| mask | Self::bit(letter))
+    }
+}
+
+macro_rules! impl_bitword {
+    ($($t:ty),*) => {$(
+        impl Bitword for $t {
+            const BITS: usize = <$t>::BITS as usize;
+            const ZERO: Self = 0;
+
+            fn bit(letter: usize) -> Self {
+                1 << letter
+            }
+
+            fn count(self) -> usize {
+                self.count_ones() as usize
+            }
+        }
+    )*};
+}
+
+impl_bitword!(u32, u64, u128);
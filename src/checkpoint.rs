@@ -0,0 +1,188 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write as _,
+    io::{self, Write},
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{error::Error, shard::Shard, sink::SolutionSink, solution::Solution};
+
+/// The roots of a checkpointed run that were done, with their solutions, read back from its log
+/// to resume the run. See [`Solver::run_checkpointed`](crate::Solver::run_checkpointed).
+///
+/// The log starts with the fingerprint and the shard of the run. Every root done is appended
+/// in one piece, as a `# root i` line, its class-level solutions in the text format and a
+/// `# done i` line, so a root cut off by a crash is searched again on resume.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub(crate) fingerprint: u64,
+    pub(crate) shard: Option<Shard>,
+    /// The solutions of every root done, by the position of the root.
+    pub(crate) roots: BTreeMap<usize, Vec<String>>,
+}
+
+impl Checkpoint {
+    /// Reads a log, ignoring roots that were not written in full.
+    pub fn parse<I>(lines: I) -> Result<Self, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut fingerprint = None;
+        let mut shard = None;
+        let mut roots = BTreeMap::new();
+        let mut root: Option<(usize, Vec<String>)> = None;
+        for (i, line) in lines.into_iter().enumerate() {
+            let line = String::from_utf8_lossy(line.as_ref());
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some(comment) = line.strip_prefix('#') else {
+                // rows outside of a root are left over from a crash
+                if let Some((_, solutions)) = &mut root {
+                    solutions.push(line.to_owned());
+                }
+                continue;
+            };
+            match Marker::parse(comment) {
+                Some(Marker::Fingerprint(value)) => fingerprint = Some(value),
+                Some(Marker::Shard(value)) => shard = Some(value),
+                Some(Marker::Root(index)) => root = Some((index, Vec::new())),
+                Some(Marker::Done(done)) => match root.take() {
+                    Some((root, solutions)) if root == done => {
+                        roots.insert(root, solutions);
+                    }
+                    _ => {}
+                },
+                // a marker cut off by a crash ends the root it belongs to
+                None if fingerprint.is_some() => root = None,
+                None => {
+                    return Err(Error::Checkpoint(format!(
+                        "line {}: {line:?} is not understood",
+                        i + 1
+                    )))
+                }
+            }
+        }
+        Ok(Checkpoint {
+            fingerprint: fingerprint
+                .ok_or_else(|| Error::Checkpoint("the log has no fingerprint".to_owned()))?,
+            shard,
+            roots,
+        })
+    }
+
+    /// The [`Solver::fingerprint`](crate::Solver::fingerprint) of the run.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn roots_done(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// The start of the log of a run.
+    pub(crate) fn header(fingerprint: u64, shard: Option<Shard>) -> String {
+        let mut header = format!("# fingerprint {fingerprint:016x}\n");
+        if let Some(shard) = shard {
+            writeln!(header, "# shard {shard}").unwrap();
+        }
+        header
+    }
+
+    /// The class-level solutions of every root done, in the text format.
+    pub(crate) fn solutions(&self) -> impl Iterator<Item = &str> {
+        self.roots.values().flatten().map(String::as_str)
+    }
+}
+
+/// A `#` line of the log.
+enum Marker {
+    Fingerprint(u64),
+    Shard(Shard),
+    Root(usize),
+    Done(usize),
+}
+
+impl Marker {
+    /// Parses the text after the `#`.
+    fn parse(comment: &str) -> Option<Marker> {
+        let (key, value) = comment.trim().split_once(' ')?;
+        match key {
+            "fingerprint" => u64::from_str_radix(value, 16).ok().map(Marker::Fingerprint),
+            "shard" => value.parse().ok().map(Marker::Shard),
+            "root" => value.parse().ok().map(Marker::Root),
+            "done" => value.parse().ok().map(Marker::Done),
+            _ => None,
+        }
+    }
+}
+
+/// Where a checkpointed run appends the roots it has done.
+pub(crate) struct Log<'w> {
+    writer: Mutex<Box<dyn Write + Send + 'w>>,
+    /// The first error writing to the log. Later roots are not written.
+    error: OnceLock<io::Error>,
+    /// Roots done before resuming, which are skipped.
+    done: HashSet<usize>,
+}
+
+impl<'w> Log<'w> {
+    pub(crate) fn new(writer: impl Write + Send + 'w, done: HashSet<usize>) -> Self {
+        Log {
+            writer: Mutex::new(Box::new(writer)),
+            error: OnceLock::new(),
+            done,
+        }
+    }
+
+    pub(crate) fn is_done(&self, root: usize) -> bool {
+        self.done.contains(&root)
+    }
+
+    /// Appends `root` with its class-level solutions.
+    pub(crate) fn record(&self, root: usize, solutions: &[Solution]) {
+        let mut block = format!("# root {root}\n");
+        for solution in solutions {
+            writeln!(block, "{solution}").unwrap();
+        }
+        writeln!(block, "# done {root}").unwrap();
+        if self.error.get().is_some() {
+            return;
+        }
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(e) = writer
+            .write_all(block.as_bytes())
+            .and_then(|()| writer.flush())
+        {
+            let _ = self.error.set(e);
+        }
+    }
+
+    pub(crate) fn into_error(self) -> Option<io::Error> {
+        self.error.into_inner()
+    }
+}
+
+/// Keeps the solutions of a root as they are pushed, without expanding the anagrams, so they
+/// can be written to the log before they go to the sink of the run.
+pub(crate) struct Collapsed;
+
+impl<'a> SolutionSink<'a> for Collapsed {
+    type Buffer = Vec<Solution<'a>>;
+
+    fn buffer(&self) -> Self::Buffer {
+        Vec::new()
+    }
+
+    fn push(&self, buffer: &mut Self::Buffer, solution: &Solution<'a>) {
+        buffer.push(solution.clone());
+    }
+
+    fn push_expanded(&self, buffer: &mut Self::Buffer, solution: &Solution<'a>) {
+        buffer.push(solution.clone());
+    }
+
+    fn flush(&self, _buffer: Self::Buffer) {}
+}
@@ -0,0 +1,144 @@
+use std::{fmt::Display, sync::Mutex};
+
+use crate::{shard::Shard, sink::SolutionSink, solution::Solution, stop::StopReason, word::Word};
+
+/// How many solutions each required or excluded word eliminates, counting every combination of
+/// anagrams as its own solution.
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintReport {
+    /// Solutions without the word constraints.
+    pub unconstrained: u64,
+    /// Solutions that satisfy every word constraint.
+    pub remaining: u64,
+    /// Every required word along with the number of solutions without it.
+    pub required: Vec<(String, u64)>,
+    /// Every excluded word along with the number of solutions with it.
+    pub excluded: Vec<(String, u64)>,
+    /// The shard searched, if the counts are only of the solutions in one shard.
+    pub shard: Option<Shard>,
+    /// Why the search ended early, if it did, in which case the counts are partial.
+    pub stopped: Option<StopReason>,
+}
+
+impl Display for ConstraintReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} solutions satisfy the word constraints",
+            self.remaining, self.unconstrained
+        )?;
+        if let Some(shard) = self.shard {
+            write!(f, " in shard {shard}")?;
+        }
+        if let Some(reason) = self.stopped {
+            write!(f, " (partial, {reason})")?;
+        }
+        for (word, eliminated) in &self.required {
+            write!(f, "\n  requiring {word} eliminates {eliminated}")?;
+        }
+        for (word, eliminated) in &self.excluded {
+            write!(f, "\n  excluding {word} eliminates {eliminated}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Counts {
+    unconstrained: u64,
+    remaining: u64,
+    /// Eliminated solutions per required word followed by per excluded word.
+    eliminated: Vec<u64>,
+}
+
+/// Counts the class-level solutions of an unconstrained search against the word constraints.
+pub(crate) struct ConstraintCounter<'c> {
+    required: &'c [Word],
+    excluded: &'c [Word],
+    counts: Mutex<Counts>,
+}
+
+impl<'c> ConstraintCounter<'c> {
+    pub(crate) fn new(required: &'c [Word], excluded: &'c [Word]) -> Self {
+        ConstraintCounter {
+            required,
+            excluded,
+            counts: Mutex::new(Counts {
+                eliminated: vec![0; required.len() + excluded.len()],
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub(crate) fn into_report(
+        self,
+        shard: Option<Shard>,
+        stopped: Option<StopReason>,
+    ) -> ConstraintReport {
+        let counts = self.counts.into_inner().unwrap();
+        let (required, excluded) = counts.eliminated.split_at(self.required.len());
+        let report = |words: &[Word], eliminated: &[u64]| {
+            let words = words.iter().map(|word| word.to_string());
+            words.zip(eliminated.iter().copied()).collect()
+        };
+        ConstraintReport {
+            unconstrained: counts.unconstrained,
+            remaining: counts.remaining,
+            required: report(self.required, required),
+            excluded: report(self.excluded, excluded),
+            shard,
+            stopped,
+        }
+    }
+}
+
+impl<'a> SolutionSink<'a> for ConstraintCounter<'_> {
+    type Buffer = Counts;
+
+    fn buffer(&self) -> Self::Buffer {
+        Counts {
+            eliminated: vec![0; self.required.len() + self.excluded.len()],
+            ..Default::default()
+        }
+    }
+
+    fn push(&self, buffer: &mut Self::Buffer, solution: &Solution<'a>) {
+        let combinations = solution.combinations();
+        // number of combinations with `word` in them
+        let with = |word: &Word| {
+            let mut anagrams = solution.anagrams().iter();
+            anagrams
+                .find(|words| words.contains(word))
+                .map_or(0, |words| combinations / words.len() as u64)
+        };
+
+        buffer.unconstrained += combinations;
+        let required = self.required.iter().map(|word| combinations - with(word));
+        let excluded = self.excluded.iter().map(with);
+        for (eliminated, count) in buffer.eliminated.iter_mut().zip(required.chain(excluded)) {
+            *eliminated += count;
+        }
+
+        if self.required.iter().all(|word| with(word) > 0) {
+            buffer.remaining += solution
+                .anagrams()
+                .iter()
+                .map(
+                    |words| match words.iter().any(|w| self.required.contains(w)) {
+                        true => 1,
+                        false => words.iter().filter(|w| !self.excluded.contains(w)).count() as u64,
+                    },
+                )
+                .product::<u64>();
+        }
+    }
+
+    fn flush(&self, buffer: Self::Buffer) {
+        let mut counts = self.counts.lock().unwrap();
+        counts.unconstrained += buffer.unconstrained;
+        counts.remaining += buffer.remaining;
+        for (total, count) in counts.eliminated.iter_mut().zip(buffer.eliminated) {
+            *total += count;
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The words can not cover the alphabet without overlapping, or one of the sizes is zero.
+    InvalidPuzzle(String),
+    /// A letter outside of the alphabet was given as a constraint.
+    InvalidLetter(char),
+    /// A dictionary line has characters outside of the alphabet while building strictly.
+    InvalidWord { line: usize, word: String },
+    /// The alphabet is empty, too large, or has repeated letters or whitespace.
+    InvalidAlphabet(String),
+    /// The required and excluded words contradict each other or the dictionary.
+    InvalidConstraint(String),
+    /// A line of a frequency list is not a word followed by a count.
+    InvalidFrequency { line: usize, text: String },
+    /// A shard is not of the form i/n, or its manifest is malformed.
+    InvalidShard(String),
+    /// A line of a solution file is not a solution of the puzzle.
+    InvalidSolution { line: usize, reason: String },
+    /// A checkpoint log is malformed or belongs to a different run, or can not be written.
+    Checkpoint(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidPuzzle(reason) => write!(f, "invalid puzzle: {reason}"),
+            Error::InvalidLetter(letter) => write!(f, "{letter:?} is not in the alphabet"),
+            Error::InvalidWord { line, word } => {
+                write!(
+                    f,
+                    "line {line}: {word:?} has characters outside of the alphabet"
+                )
+            }
+            Error::InvalidAlphabet(reason) => write!(f, "invalid alphabet: {reason}"),
+            Error::InvalidConstraint(reason) => write!(f, "invalid constraint: {reason}"),
+            Error::InvalidFrequency { line, text } => {
+                write!(f, "line {line}: {text:?} is not a word followed by a count")
+            }
+            Error::InvalidShard(reason) => write!(f, "invalid shard: {reason}"),
+            Error::InvalidSolution { line, reason } => write!(f, "line {line}: {reason}"),
+            Error::Checkpoint(reason) => write!(f, "checkpoint: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
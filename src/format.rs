@@ -0,0 +1,99 @@
+use std::io::{self, Write};
+
+use itertools::Itertools;
+
+use crate::solution::Solution;
+
+/// How [`WriterSink`](crate::WriterSink) writes solutions, one per line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Words separated by spaces, anagrams by /, followed by the unused letters in brackets.
+    #[default]
+    Text,
+    /// A JSON object per line with the words, the unused letters and the anagrams of each word.
+    JsonLines,
+    /// A column per word followed by the unused letters, with anagrams separated by /.
+    Csv,
+    /// Like [`Format::Csv`], separated by tabs.
+    Tsv,
+}
+
+impl Format {
+    /// Writes the header line, if the format has one.
+    pub fn write_header<W: Write>(self, writer: &mut W, word_count: usize) -> io::Result<()> {
+        let separator = match self {
+            Format::Text | Format::JsonLines => return Ok(()),
+            Format::Csv => ",",
+            Format::Tsv => "\t",
+        };
+        let columns = (1..=word_count).map(|i| format!("word{i}"));
+        writeln!(writer, "{}{separator}missing", columns.format(separator))
+    }
+
+    /// Writes a solution. CSV and TSV rows are padded to `word_count` words, the most words a
+    /// solution can have.
+    pub fn write<W: Write>(
+        self,
+        writer: &mut W,
+        solution: &Solution,
+        word_count: usize,
+    ) -> io::Result<()> {
+        match self {
+            Format::Text => writeln!(writer, "{solution}"),
+            Format::JsonLines => {
+                let words = solution.words().map(|word| json_string(&word.to_string()));
+                let anagrams = solution.anagrams().iter().map(|words| {
+                    let words = words.iter().map(|word| json_string(&word.to_string()));
+                    format!("[{}]", words.format(","))
+                });
+                writeln!(
+                    writer,
+                    r#"{{"words":[{}],"missing":{},"anagrams":[{}]}}"#,
+                    words.format(","),
+                    json_string(&solution.missing().collect::<String>()),
+                    anagrams.format(",")
+                )
+            }
+            Format::Csv | Format::Tsv => {
+                let separator = if self == Format::Csv { "," } else { "\t" };
+                let anagrams = solution
+                    .anagrams()
+                    .iter()
+                    .map(|words| quote_field(words.iter().join("/"), separator));
+                let padding = word_count.saturating_sub(solution.anagrams().len());
+                writeln!(
+                    writer,
+                    "{}{}{separator}{}",
+                    anagrams.format(separator),
+                    separator.repeat(padding),
+                    quote_field(solution.missing().collect(), separator)
+                )
+            }
+        }
+    }
+}
+
+/// Quotes a CSV or TSV field as RFC 4180 does, if it contains the separator, a quote or a
+/// newline. Custom alphabets may have any of them as letters.
+fn quote_field(field: String, separator: &str) -> String {
+    let special = |c: char| separator.contains(c) || matches!(c, '"' | '\n' | '\r');
+    match field.contains(special) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field,
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
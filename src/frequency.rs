@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::{alphabet::Alphabet, error::Error, normalize::normalize, solution::Solution};
+
+/// How often words occur in some body of text, read from a list with a word and a count on
+/// every line, separated by a tab or spaces.
+#[derive(Clone, Debug, Default)]
+pub struct Frequencies {
+    counts: Vec<(String, u64)>,
+}
+
+impl Frequencies {
+    /// Parses the lines of a frequency list. Empty lines are skipped.
+    pub fn parse<I>(lines: I) -> Result<Frequencies, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut counts = Vec::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            let line = String::from_utf8_lossy(line.as_ref());
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let count = line
+                .rsplit_once(char::is_whitespace)
+                .and_then(|(word, count)| Some((word.trim(), count.parse().ok()?)));
+            match count {
+                Some((word, count)) => counts.push((word.to_owned(), count)),
+                None => {
+                    return Err(Error::InvalidFrequency {
+                        line: i + 1,
+                        text: line.to_owned(),
+                    })
+                }
+            }
+        }
+        Ok(Frequencies { counts })
+    }
+
+    /// Number of words listed.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The counts by normalised word, adding up the counts of words that normalise the same.
+    pub(crate) fn normalized(
+        &self,
+        alphabet: &Alphabet,
+        strip_diacritics: bool,
+    ) -> HashMap<String, u64> {
+        let mut counts = HashMap::with_capacity(self.counts.len());
+        for (word, count) in &self.counts {
+            let word = normalize(word, alphabet, strip_diacritics);
+            let total: &mut u64 = counts.entry(word.into_owned()).or_default();
+            *total = total.saturating_add(*count);
+        }
+        counts
+    }
+}
+
+/// How to combine the frequencies of the words of a solution into a score. Higher scores are
+/// better. Each position counts with its most common anagram.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Score {
+    /// The frequency of the rarest word, so a single obscure word sinks the whole solution.
+    #[default]
+    Min,
+    /// The sum of the logarithms of the frequencies, i.e. the likelihood of the words.
+    LogSum,
+}
+
+impl Score {
+    pub(crate) fn of(self, solution: &Solution) -> f64 {
+        let frequencies = solution.anagrams().iter().map(|words| {
+            let frequency = words.iter().map(|word| word.frequency()).max();
+            frequency.unwrap_or(0) as f64
+        });
+        match self {
+            Score::Min => frequencies.fold(f64::INFINITY, f64::min),
+            Score::LogSum => frequencies.map(f64::ln_1p).sum(),
+        }
+    }
+}
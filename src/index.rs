@@ -0,0 +1,181 @@
+use std::ops::Index;
+
+use itertools::Itertools;
+use rayon::prelude::*;
+
+use crate::{alphabet::Alphabet, bitword::Bitword, error::Error, order::LetterOrder, word::Word};
+
+/// All words sharing the same set of letters. The solver searches over classes, and every
+/// class-level solution is expanded into the concrete word combinations when emitted.
+pub(crate) struct AnagramClass<B> {
+    /// The letters of the words after the letter order transform.
+    pub(crate) bitword: B,
+    /// Number of letters.
+    pub(crate) len: usize,
+    pub(crate) words: Vec<Word>,
+}
+
+pub(crate) fn next_free_letter<B: Bitword>(filter: B, letters: usize) -> Option<usize> {
+    (0..letters).rev().find(|n| !filter.is_set(*n))
+}
+
+/// Anagram classes bucketed by their most significant letter after the frequency transform.
+pub(crate) struct WordIndex<B> {
+    buckets: Vec<Vec<AnagramClass<B>>>,
+    transform: Vec<usize>,
+}
+
+impl<B: Bitword> WordIndex<B> {
+    /// Number of letters in the alphabet.
+    pub(crate) fn len(&self) -> usize {
+        self.transform.len()
+    }
+
+    /// Maps a bitword of the original alphabet to the transformed one.
+    pub(crate) fn transform(&self, bitword: u128) -> B {
+        (0..self.len())
+            .filter(|letter| bitword & (1 << letter) != 0)
+            .fold(B::ZERO, |transformed, letter| {
+                transformed | B::bit(self.transform[letter])
+            })
+    }
+
+    /// Finds `word` in the index.
+    pub(crate) fn find(&self, word: &Word) -> Option<&Word> {
+        self.anagrams(word)?.iter().find(|w| *w == word)
+    }
+
+    /// The words of the anagram class with the letters of `word`.
+    pub(crate) fn anagrams(&self, word: &Word) -> Option<&[Word]> {
+        let bitword = self.transform(word.bitword);
+        let msl = (0..self.len()).rev().find(|n| bitword.is_set(*n))?;
+        let class = self.buckets[msl].iter().find(|c| c.bitword == bitword)?;
+        Some(&class.words)
+    }
+
+    /// Every word in the index.
+    pub(crate) fn words(&self) -> impl Iterator<Item = &Word> {
+        self.buckets.iter().flatten().flat_map(|class| &class.words)
+    }
+
+    /// Every anagram class in the index.
+    pub(crate) fn classes(&self) -> impl Iterator<Item = &AnagramClass<B>> {
+        self.buckets.iter().flatten()
+    }
+
+    /// The original letter and the number of anagram classes of every bucket, in the order the
+    /// search covers the letters.
+    pub(crate) fn bucket_sizes(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.len()).rev().map(|letter| {
+            let original = self.restore(B::bit(letter)).trailing_zeros() as usize;
+            (original, self.buckets[letter].len())
+        })
+    }
+
+    /// Maps a transformed bitword back to the original alphabet.
+    pub(crate) fn restore(&self, bitword: B) -> u128 {
+        (0..self.len())
+            .filter(|letter| bitword.is_set(self.transform[*letter]))
+            .fold(0, |restored, letter| restored | 1 << letter)
+    }
+}
+
+impl<B> Index<usize> for WordIndex<B> {
+    type Output = Vec<AnagramClass<B>>;
+
+    fn index(&self, letter: usize) -> &Self::Output {
+        &self.buckets[letter]
+    }
+}
+
+/// Evaluates `$body` with `$index` bound to the word index inside an [`AnyWordIndex`].
+macro_rules! with_index {
+    ($any:expr, $index:ident => $body:expr) => {
+        match $any {
+            AnyWordIndex::U32($index) => $body,
+            AnyWordIndex::U64($index) => $body,
+            AnyWordIndex::U128($index) => $body,
+        }
+    };
+}
+pub(crate) use with_index;
+
+/// A word index with the narrowest bitword that fits the alphabet.
+pub(crate) enum AnyWordIndex {
+    U32(WordIndex<u32>),
+    U64(WordIndex<u64>),
+    U128(WordIndex<u128>),
+}
+
+impl AnyWordIndex {
+    pub(crate) fn new(
+        words: Vec<Word>,
+        alphabet: &Alphabet,
+        order: &dyn LetterOrder,
+    ) -> Result<Self, Error> {
+        Ok(match alphabet.len() {
+            ..=32 => AnyWordIndex::U32(create_word_index(words, alphabet, order)?),
+            33..=64 => AnyWordIndex::U64(create_word_index(words, alphabet, order)?),
+            _ => AnyWordIndex::U128(create_word_index(words, alphabet, order)?),
+        })
+    }
+
+    pub(crate) fn words(&self) -> Vec<Word> {
+        with_index!(self, index => index.words().cloned().collect())
+    }
+
+    pub(crate) fn bucket_sizes(&self) -> Vec<(usize, usize)> {
+        with_index!(self, index => index.bucket_sizes().collect())
+    }
+}
+
+pub(crate) fn create_word_index<B: Bitword>(
+    mut words: Vec<Word>,
+    alphabet: &Alphabet,
+    order: &dyn LetterOrder,
+) -> Result<WordIndex<B>, Error> {
+    let alphabet_len = alphabet.len();
+    assert!(alphabet_len <= B::BITS);
+    words.par_sort_unstable_by(|a, b| (a.bitword, a).cmp(&(b.bitword, b)));
+    words.dedup();
+
+    let classes = words
+        .chunk_by(|a, b| a.bitword == b.bitword)
+        .map(|words| (words[0].bitword, words.to_vec()))
+        .collect_vec();
+
+    let order = order.order(
+        &classes.iter().map(|(bitword, _)| *bitword).collect_vec(),
+        alphabet,
+    );
+    if order.iter().copied().sorted_unstable().ne(0..alphabet_len) {
+        return Err(Error::InvalidAlphabet(
+            "the letter order must have every letter of the alphabet once".to_owned(),
+        ));
+    }
+
+    // create transform where the letter the search covers first is the last one, the second the
+    // second to last, ..., the last 0
+    let mut transform = vec![0; alphabet_len];
+    for (i, letter) in order.into_iter().enumerate() {
+        transform[letter] = alphabet_len - 1 - i;
+    }
+
+    let mut word_index = WordIndex {
+        buckets: (0..alphabet_len).map(|_| Vec::new()).collect(),
+        transform,
+    };
+    for (bitword, words) in classes {
+        let bitword: B = word_index.transform(bitword);
+        let msl = (0..alphabet_len)
+            .rev()
+            .find(|n| bitword.is_set(*n))
+            .unwrap(); // most significant letter
+        word_index.buckets[msl].push(AnagramClass {
+            bitword,
+            len: words[0].len(),
+            words,
+        });
+    }
+    Ok(word_index)
+}
@@ -0,0 +1,49 @@
+//! A solver for Matt Parker's five five-letter words challenge, generalised to any number of
+//! words of any length.
+//!
+//! ```no_run
+//! let dictionary = std::fs::read_to_string("words_alpha.txt").unwrap();
+//! let solver = five_five::Solver::builder().build(dictionary.lines()).unwrap();
+//! solver.for_each(|solution| println!("{solution}"));
+//! ```
+
+mod alphabet;
+mod bitword;
+mod checkpoint;
+mod constraints;
+mod error;
+mod format;
+mod frequency;
+mod index;
+mod normalize;
+mod order;
+mod progress;
+mod report;
+mod search;
+mod shard;
+mod sink;
+mod solution;
+mod solver;
+mod stats;
+mod stop;
+mod verify;
+mod word;
+
+pub use alphabet::Alphabet;
+pub use checkpoint::Checkpoint;
+pub use constraints::ConstraintReport;
+pub use error::Error;
+pub use format::Format;
+pub use frequency::{Frequencies, Score};
+pub use normalize::{InputSummary, Rejection};
+pub use order::{BucketSize, Explicit, Frequency, LetterOrder, RarestLetter};
+pub use progress::Progress;
+pub use report::RunReport;
+pub use shard::{Shard, ShardManifest};
+pub use sink::{ChannelSink, CountSink, SolutionSink, VecSink, WriterSink};
+pub use solution::Solution;
+pub use solver::{Solver, SolverBuilder};
+pub use stats::{NodeCounts, SearchStats};
+pub use stop::{CancellationToken, StopReason};
+pub use verify::Verification;
+pub use word::Word;
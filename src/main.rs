@@ -1,181 +1,599 @@
 use std::{
-    cmp::max,
-    fmt::{Debug, Display},
-    fs::{self, File},
-    io::{Result, Write},
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
     sync::Mutex,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use itertools::Itertools;
-use rayon::prelude::*;
-
-#[derive(Clone, Default)]
-struct Word {
-    bitword: u32,
-    bytes: [u8; 5],
-}
-
-impl Word {
-    fn new(bytes: &[u8]) -> Option<Word> {
-        let bytes: [u8; 5] = bytes.try_into().ok()?;
-        let mut bitword = 0;
-        let mut len = 0;
-        for letter in bytes {
-            debug_assert!(letter >= b'a');
-            debug_assert!(letter <= b'z');
-            let offset = letter - b'a';
-            if bitword & (1 << offset) == 0 {
-                bitword |= 1 << offset;
-                len += 1
-            }
-        }
-        match len {
-            5 => Some(Word { bitword, bytes }),
-            _ => None,
+use clap::{Parser, Subcommand, ValueEnum};
+use five_five::{
+    Alphabet, BucketSize, Checkpoint, CountSink, Explicit, Format, Frequencies, Progress,
+    RarestLetter, RunReport, Score, Shard, ShardManifest, SolutionSink, Solver, WriterSink,
+};
+
+/// Finds sets of words with no letters in common, like five five-letter words covering 25
+/// letters of the alphabet.
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Dictionary with one word per line, or - for stdin
+    #[arg(short, long, default_value = "words_alpha.txt", global = true)]
+    input: PathBuf,
+
+    /// Where to write the solutions, or - for stdout
+    #[arg(short, long, default_value = "solutions.txt", global = true)]
+    output: PathBuf,
+
+    /// Number of distinct letters in every word, or a comma separated list of lengths to mix
+    #[arg(
+        global = true,
+        short = 'l',
+        long,
+        value_delimiter = ',',
+        default_value = "5"
+    )]
+    word_len: Vec<usize>,
+
+    /// Number of words in every solution, defaults to 5 for a single word length and to any
+    /// number for mixed lengths
+    #[arg(short = 'n', long, global = true)]
+    word_count: Option<usize>,
+
+    /// Number of letters covered by every solution, required for mixed word lengths
+    #[arg(long, global = true)]
+    letters: Option<usize>,
+
+    /// Number of worker threads, defaults to one per core
+    #[arg(short = 'j', long, global = true)]
+    threads: Option<usize>,
+
+    /// How to write the solutions
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    format: OutputFormat,
+
+    /// Write one solution per combination of anagram classes instead of one per combination of
+    /// words, with anagrams separated by /
+    #[arg(long)]
+    collapse: bool,
+
+    /// Only find solutions that leave all of these letters unused
+    #[arg(short, long, default_value = "", global = true)]
+    missing: String,
+
+    /// Only use these letters, leaving the rest of the alphabet unused
+    #[arg(short, long, global = true)]
+    pool: Option<String>,
+
+    /// Only find solutions containing these words, separated by commas
+    #[arg(short, long, value_delimiter = ',', global = true)]
+    require: Vec<String>,
+
+    /// Leave these words out of the dictionary, separated by commas
+    #[arg(short = 'x', long, value_delimiter = ',', global = true)]
+    exclude: Vec<String>,
+
+    /// Leave the words in this file out of the dictionary, one per line
+    #[arg(long, global = true)]
+    exclude_file: Option<PathBuf>,
+
+    /// Letters words are made of: english, danish, norwegian, swedish, german, spanish, or the
+    /// letters themselves in alphabetical order
+    #[arg(short, long, default_value = "english", global = true)]
+    alphabet: Alphabet,
+
+    /// Replace accented letters in the dictionary that are not in the alphabet by their base
+    /// letter, e.g. é by e
+    #[arg(long, global = true)]
+    strip_diacritics: bool,
+
+    /// Fail on dictionary lines with characters outside of the alphabet instead of skipping them
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Only count the solutions instead of writing them
+    #[arg(short, long)]
+    count: bool,
+
+    /// Write the solutions in canonical order, with the words of each solution sorted and the
+    /// solutions sorted lexicographically
+    #[arg(short, long, conflicts_with = "count")]
+    sorted: bool,
+
+    /// Write the K combinations of words covering the most letters, best first, for when no
+    /// solution covers all of them. One word may share letters with the others, each shared
+    /// letter counting against the letters covered
+    #[arg(long, value_name = "K", conflicts_with = "sorted")]
+    best: Option<usize>,
+
+    /// Word frequency list with a word and a count on every line, for ranking the solutions
+    #[arg(long)]
+    frequencies: Option<PathBuf>,
+
+    /// Write the solutions best first by how common their words are
+    #[arg(long, value_enum, requires = "frequencies", conflicts_with_all = ["count", "sorted", "best"])]
+    rank: Option<Ranking>,
+
+    /// Only write the K best ranked solutions
+    #[arg(long, value_name = "K", requires = "rank")]
+    top: Option<usize>,
+
+    /// Stop after writing or counting N solutions
+    #[arg(long, value_name = "N")]
+    limit: Option<u64>,
+
+    /// Stop the search after this many seconds, keeping the solutions found so far
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    timeout: Option<Duration>,
+
+    /// Order in which the search covers the letters: frequency, rarest-letter, bucket-size, or
+    /// every letter of the alphabet in the order to cover them
+    #[arg(long, default_value = "frequency", global = true)]
+    letter_order: String,
+
+    /// Count the nodes of the search tree by depth and by letter and add them to the report
+    #[arg(long)]
+    stats: bool,
+
+    /// Only search the I-th of N deterministic slices of the search tree, writing a manifest
+    /// next to the output for the merge command
+    #[arg(long, value_name = "I/N", conflicts_with_all = ["best", "rank", "format", "collapse"])]
+    shard: Option<Shard>,
+
+    /// Append every root of the search tree done, with its solutions, to this log, resuming
+    /// from it if it exists
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["sorted", "rank", "best"])]
+    checkpoint: Option<PathBuf>,
+
+    /// Show how many roots of the search tree are done, the solutions found so far and a rough
+    /// estimate of the time left
+    #[arg(long)]
+    progress: bool,
+
+    /// Write the time taken by each phase and the sizes of the dictionary and the search to
+    /// this file as JSON
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check a file of solutions against the dictionary and a fresh solve, reporting invalid
+    /// rows, duplicates and missing solutions
+    Verify {
+        /// Solutions in the text format, one per line, or - for stdin
+        solutions: PathBuf,
+    },
+    /// Combine the outputs of every shard of a run into one sorted solution file, checking that
+    /// no shard is missing or incomplete
+    Merge {
+        /// Outputs of the shards, each with its manifest next to it
+        #[arg(required = true)]
+        shards: Vec<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// Words separated by spaces followed by the unused letters in brackets
+    Text,
+    /// A JSON object per line with the words, the unused letters and the anagrams
+    Jsonl,
+    /// Comma separated with a header
+    Csv,
+    /// Tab separated with a header
+    Tsv,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Ranking {
+    /// By the frequency of the rarest word
+    Min,
+    /// By the sum of the logarithms of the word frequencies
+    LogSum,
+}
+
+impl From<Ranking> for Score {
+    fn from(ranking: Ranking) -> Self {
+        match ranking {
+            Ranking::Min => Score::Min,
+            Ranking::LogSum => Score::LogSum,
         }
     }
+}
 
-    fn transform(mut self, t: [usize; 26]) -> (usize, Self) {
-        let mut msl = 0; // most significant letter
-        self.bitword = 0;
-        for letter in self.bytes {
-            let offset = t[(letter - b'a') as usize];
-            msl = max(msl, offset);
-            self.bitword |= 1 << offset;
+impl From<OutputFormat> for Format {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Text => Format::Text,
+            OutputFormat::Jsonl => Format::JsonLines,
+            OutputFormat::Csv => Format::Csv,
+            OutputFormat::Tsv => Format::Tsv,
         }
-        (msl, self)
     }
 }
 
-impl Debug for Word {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:#b} {self}", self.bitword)
-    }
+fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn read_input(path: &Path, what: &str) -> Result<Vec<u8>, String> {
+    let mut dictionary = Vec::new();
+    let read = if is_stdio(path) {
+        io::stdin().read_to_end(&mut dictionary).map(|_| ())
+    } else {
+        fs::read(path).map(|bytes| dictionary = bytes)
+    };
+    read.map_err(|e| format!("could not read {what} {}: {e}", path.display()))?;
+    Ok(dictionary)
 }
 
-impl Display for Word {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&String::from_utf8_lossy(&self.bytes))
+fn lines(dictionary: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let dictionary = dictionary.strip_suffix(b"\n").unwrap_or(dictionary);
+    dictionary.split(|b| *b == b'\n')
+}
+
+fn create_output(path: &Path) -> Result<Box<dyn Write + Send>, String> {
+    if is_stdio(path) {
+        return Ok(Box::new(io::stdout()));
     }
+    let file = File::create(path)
+        .map_err(|e| format!("could not create output {}: {e}", path.display()))?;
+    Ok(Box::new(file))
 }
 
-fn next_free_letter(filter: u32) -> Option<usize> {
-    (0..26).rev().filter(|n| filter & (1 << n) == 0).next()
+/// Rewrites a progress line on stderr at most ten times a second.
+fn progress_line() -> impl Fn(&Progress) + Send + Sync {
+    let last = Mutex::new(None::<Instant>);
+    move |progress| {
+        let mut last = last.lock().unwrap();
+        let done = progress.roots_done == progress.roots;
+        if !done && last.is_some_and(|last| last.elapsed() < Duration::from_millis(100)) {
+            return;
+        }
+        // end the line once every root is done, so the rest of the output starts on a new one
+        eprint!("\r{progress}\x1b[K{}", if done { "\n" } else { "" });
+        *last = Some(Instant::now());
+    }
 }
 
-type WordIndex = [Vec<Word>; 26];
+fn parse_seconds(seconds: &str) -> Result<Duration, String> {
+    let seconds: f64 = seconds.parse().map_err(|e| format!("{e}"))?;
+    Duration::try_from_secs_f64(seconds).map_err(|e| format!("{e}"))
+}
 
-fn create_word_index(mut words: Vec<Word>) -> WordIndex {
-    words.par_sort_unstable_by_key(|w| w.bitword);
-    words.dedup_by_key(|w| w.bitword);
+fn run(args: Args) -> Result<(), String> {
+    let start = Instant::now();
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|e| format!("could not start {threads} threads: {e}"))?;
+    }
 
-    let mut freqs = [0; 26];
-    for word in &words {
-        for b in word.bytes {
-            freqs[(b - b'a') as usize] += 1;
+    let dictionary = read_input(&args.input, "dictionary")?;
+    let read = start.elapsed();
+    let mut excluded = args.exclude.clone();
+    if let Some(path) = &args.exclude_file {
+        let file = read_input(path, "exclude file")?;
+        excluded.extend(lines(&file).map(|line| String::from_utf8_lossy(line).into_owned()));
+    }
+    let mut builder = Solver::builder().word_lens(args.word_len.iter().copied());
+    if let Some(word_count) = args.word_count {
+        builder = builder.word_count(word_count);
+    }
+    if let Some(letters) = args.letters {
+        builder = builder.letters(letters);
+    }
+    if let Some(pool) = &args.pool {
+        builder = builder.pool(pool);
+    }
+    builder = match args.letter_order.as_str() {
+        "frequency" => builder,
+        "rarest-letter" => builder.letter_order(RarestLetter),
+        "bucket-size" => builder.letter_order(BucketSize),
+        letters if letters.chars().count() == args.alphabet.len() => {
+            builder.letter_order(Explicit(letters.to_owned()))
+        }
+        name => {
+            return Err(format!(
+                "unknown letter order {name:?}, expected frequency, rarest-letter, bucket-size or \
+                 every letter of the alphabet"
+            ))
+        }
+    };
+    if let Some(path) = &args.frequencies {
+        let file = read_input(path, "frequency list")?;
+        let frequencies = Frequencies::parse(lines(&file)).map_err(|e| e.to_string())?;
+        builder = builder.frequencies(frequencies);
+    }
+    if let Some(ranking) = args.rank {
+        builder = builder.rank(ranking.into());
+    }
+    if let Some(top) = args.top {
+        builder = builder.top(top);
+    }
+    if let Some(limit) = args.limit {
+        builder = builder.limit(limit);
+    }
+    if let Some(timeout) = args.timeout {
+        builder = builder.timeout(timeout);
+    }
+    if args.progress {
+        builder = builder.progress(progress_line());
+    }
+    if let Some(shard) = args.shard {
+        if args.command.is_none() && !args.count && is_stdio(&args.output) {
+            return Err("a shard needs an output file to write its manifest next to".to_owned());
         }
+        builder = builder.shard(shard);
     }
+    let solver = builder
+        .expand_anagrams(!args.collapse)
+        .sorted(args.sorted)
+        .missing(&args.missing)
+        .require(&args.require)
+        .exclude(&excluded)
+        .alphabet(args.alphabet.clone())
+        .strip_diacritics(args.strip_diacritics)
+        .strict(args.strict)
+        .instrument(args.stats)
+        .build(lines(&dictionary))
+        .map_err(|e| e.to_string())?;
 
-    // create transform where least frequent letter is 25, second least 24, ..., most frequent 0
-    let transform: [usize; 26] = freqs
-        .into_iter()
-        .enumerate()
-        .sorted_unstable_by_key(|(_i, f)| *f)
-        .rev()
-        .map(|(i, _f)| i) // letters now in sorted order from most to least frequent
-        .enumerate()
-        .sorted_unstable_by_key(|(_i_transformed, i_letter)| *i_letter) // sort again, s.t. the letter corresponds with index in transform
-        .map(|(i_transformed, _i_letter)| i_transformed)
-        .collect_vec()
-        .try_into()
-        .unwrap();
-
-    let mut word_index: [Vec<Word>; 26] = Default::default();
-    for word in words {
-        let (msl, word) = word.transform(transform);
-        word_index[msl].push(word);
-    }
-    word_index
-}
-
-fn solve(words: Vec<Word>, output: File) {
-    let word_index = create_word_index(words);
-    let output = Mutex::new(output);
-
-    word_index[25].par_iter().for_each(|word| {
-        let mut solution: [Word; 5] = Default::default();
-        solution[0] = word.clone();
-        solve14(&word_index, &output, word.bitword, false, &mut solution, 1);
-    });
-
-    word_index[24].par_iter().for_each(|word| {
-        let mut solution: [Word; 5] = Default::default();
-        solution[0] = word.clone();
-        solve14(
-            &word_index,
-            &output,
-            word.bitword | 1 << 25,
-            true,
-            &mut solution,
-            1,
+    let summary = solver.input_summary();
+    eprintln!("{summary}");
+    for (line, word) in summary.invalid.iter().take(10) {
+        eprintln!("  line {line}: {word:?}");
+    }
+    if summary.invalid.len() > 10 {
+        eprintln!("  and {} more", summary.invalid.len() - 10);
+    }
+
+    if let Some(Command::Verify { solutions }) = &args.command {
+        verify(&solver, solutions)?;
+        eprintln!("{} us", start.elapsed().as_micros());
+        return Ok(());
+    }
+    if let Some(Command::Merge { shards }) = &args.command {
+        merge(&args, &solver, shards)?;
+        eprintln!("{} us", start.elapsed().as_micros());
+        return Ok(());
+    }
+
+    let report = RunReport {
+        read,
+        ..solve(&args, &solver)?
+    };
+    eprintln!("{report}");
+    if let Some(path) = &args.report {
+        fs::write(path, report.to_json() + "\n")
+            .map_err(|e| format!("could not write report {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn solve(args: &Args, solver: &Solver) -> Result<RunReport, String> {
+    let report = if args.count {
+        let sink = CountSink::new();
+        let report = run_solver(args, solver, &sink)?;
+        println!(
+            "{} solutions, {} up to anagrams",
+            sink.count(),
+            sink.class_count()
         );
-    });
-}
-
-fn solve14(
-    word_index: &WordIndex,
-    output: &Mutex<File>,
-    filter: u32,
-    skipped: bool,
-    solution: &mut [Word; 5],
-    i: usize,
-) {
-    let letter = next_free_letter(filter).unwrap();
-    if i == 4 {
-        for word in &word_index[letter] {
-            if word.bitword & filter == 0 {
-                writeln!(
-                    output.lock().unwrap(),
-                    "{} {} {} {} {word}",
-                    solution[0],
-                    solution[1],
-                    solution[2],
-                    solution[3]
-                )
-                .unwrap();
-            }
-        }
+        report
     } else {
-        for word in &word_index[letter] {
-            if word.bitword & filter == 0 {
-                solution[i] = word.clone();
-                solve14(
-                    word_index,
-                    output,
-                    filter | word.bitword,
-                    skipped,
-                    solution,
-                    i + 1,
-                );
-            }
+        let write_error = |e| {
+            format!(
+                "could not write solutions to {}: {e}",
+                args.output.display()
+            )
+        };
+        let output = create_output(&args.output)?;
+        let sink = WriterSink::with_format(output, args.format.into(), solver.max_word_count())
+            .map_err(write_error)?;
+        let mut report = run_solver(args, solver, &sink)?;
+        let start = Instant::now();
+        sink.finish().map_err(write_error)?;
+        if let Some(shard) = args.shard {
+            let manifest = ShardManifest {
+                shard,
+                fingerprint: solver.fingerprint(),
+                solutions: report.solutions,
+                complete: report.stopped.is_none(),
+            };
+            let path = manifest_path(&args.output);
+            fs::write(&path, manifest.to_string())
+                .map_err(|e| format!("could not write manifest {}: {e}", path.display()))?;
         }
+        report.write = start.elapsed();
+        report
+    };
+
+    let has_word_constraints =
+        !args.require.is_empty() || !args.exclude.is_empty() || args.exclude_file.is_some();
+    if has_word_constraints {
+        eprintln!("{}", solver.constraint_report());
     }
-    if !skipped {
-        solve14(word_index, output, filter | 1 << letter, true, solution, i);
+    Ok(report)
+}
+
+fn run_solver<'a, S>(args: &Args, solver: &'a Solver, sink: &S) -> Result<RunReport, String>
+where
+    S: SolutionSink<'a>,
+{
+    match (args.best, &args.checkpoint) {
+        (Some(limit), _) => Ok(solver.run_best(limit, sink)),
+        (None, Some(path)) => run_checkpointed(solver, sink, path),
+        (None, None) => Ok(solver.run(sink)),
     }
 }
 
-fn main() -> Result<()> {
-    let start = Instant::now();
-    let words = fs::read("words_alpha.txt")?
-        .par_split(|b| *b == b'\n')
-        .filter_map(|l| Word::new(l))
-        .collect();
+/// Runs with a checkpoint log at `path`, resuming from it if it has been started.
+fn run_checkpointed<'a, S>(solver: &'a Solver, sink: &S, path: &Path) -> Result<RunReport, String>
+where
+    S: SolutionSink<'a>,
+{
+    let checkpoint_error = |e: five_five::Error| format!("{}: {e}", path.display());
+    let resume = match fs::read(path) {
+        Ok(log) if !log.is_empty() => {
+            let checkpoint = Checkpoint::parse(lines(&log)).map_err(checkpoint_error)?;
+            eprintln!(
+                "resuming from {} with {} roots done",
+                path.display(),
+                checkpoint.roots_done()
+            );
+            Some(checkpoint)
+        }
+        Ok(_) => None,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("could not read checkpoint {}: {e}", path.display())),
+    };
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("could not open checkpoint {}: {e}", path.display()))?;
+    solver
+        .run_checkpointed(sink, resume.as_ref(), log)
+        .map_err(checkpoint_error)
+}
 
-    let output = File::create("solutions.txt")?;
-    solve(words, output);
+/// Where the manifest of a shard written to `output` goes.
+fn manifest_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(".shard");
+    path.into()
+}
 
-    println!("{} us", start.elapsed().as_micros());
+fn merge(args: &Args, solver: &Solver, paths: &[PathBuf]) -> Result<(), String> {
+    let fingerprint = solver.fingerprint();
+    let mut shards: Vec<(Shard, &Path)> = Vec::new();
+    let mut solutions = Vec::new();
+    let files = paths
+        .iter()
+        .map(|path| read_input(path, "shard"))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (path, file) in paths.iter().zip(&files) {
+        let manifest_path = manifest_path(path);
+        let manifest = read_input(&manifest_path, "shard manifest")?;
+        let manifest = ShardManifest::parse(&String::from_utf8_lossy(&manifest))
+            .map_err(|e| format!("{}: {e}", manifest_path.display()))?;
+        let shard = manifest.shard;
+        if manifest.fingerprint != fingerprint {
+            return Err(format!(
+                "{} was written with a different dictionary or different settings",
+                path.display()
+            ));
+        }
+        if !manifest.complete {
+            return Err(format!(
+                "{} is incomplete, the search was stopped early",
+                path.display()
+            ));
+        }
+        if let Some((first, first_path)) =
+            shards.first().filter(|(s, _)| s.count() != shard.count())
+        {
+            return Err(format!(
+                "{} is shard {shard} but {} is shard {first}",
+                path.display(),
+                first_path.display()
+            ));
+        }
+        if let Some((_, other)) = shards.iter().find(|(s, _)| *s == shard) {
+            return Err(format!(
+                "{} and {} are both shard {shard}",
+                other.display(),
+                path.display()
+            ));
+        }
+        shards.push((shard, path));
+
+        let shard_solutions = solver
+            .parse_solutions(lines(file))
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        if shard_solutions.len() as u64 != manifest.solutions {
+            return Err(format!(
+                "{} has {} solutions instead of the {} in its manifest",
+                path.display(),
+                shard_solutions.len(),
+                manifest.solutions
+            ));
+        }
+        solutions.extend(shard_solutions);
+    }
+    let count = shards[0].0.count();
+    let missing = (1..=count)
+        .filter(|i| shards.iter().all(|(shard, _)| shard.index() != *i))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        let missing = missing.iter().map(|i| format!("{i}/{count}"));
+        return Err(format!(
+            "missing shards {}",
+            missing.collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    let found = solutions.len();
+    solutions.sort_unstable();
+    solutions.dedup();
+    let write_error = |e| {
+        format!(
+            "could not write solutions to {}: {e}",
+            args.output.display()
+        )
+    };
+    let output = create_output(&args.output)?;
+    let sink = WriterSink::with_format(output, args.format.into(), solver.max_word_count())
+        .map_err(write_error)?;
+    let mut buffer = sink.buffer();
+    for solution in &solutions {
+        sink.push(&mut buffer, solution);
+    }
+    sink.flush(buffer);
+    sink.finish().map_err(write_error)?;
+    eprintln!(
+        "{} solutions from {count} shards, {} duplicates removed",
+        solutions.len(),
+        found - solutions.len()
+    );
     Ok(())
 }
+
+fn verify(solver: &Solver, path: &Path) -> Result<(), String> {
+    let solutions = read_input(path, "solutions")?;
+    let verification = solver.verify(lines(&solutions));
+    println!("{verification}");
+    for (line, reason) in &verification.invalid {
+        println!("  line {line}: {reason}");
+    }
+    for (line, first) in &verification.duplicates {
+        println!("  line {line}: duplicate of line {first}");
+    }
+    for solution in &verification.missing {
+        println!("  missing: {solution}");
+    }
+    match verification.is_ok() {
+        true => Ok(()),
+        false => Err(format!(
+            "{} does not hold every solution once",
+            path.display()
+        )),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
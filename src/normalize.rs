@@ -0,0 +1,111 @@
+use std::{borrow::Cow, fmt::Display};
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::alphabet::Alphabet;
+
+/// Why a dictionary line was not turned into a word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    /// Nothing but whitespace.
+    Empty,
+    /// A character outside of the alphabet, like an apostrophe or a digit.
+    InvalidCharacter,
+    /// Not the number of letters asked for.
+    WrongLength,
+    /// A letter appears more than once.
+    RepeatedLetter,
+}
+
+/// How the lines of the dictionary were handled.
+#[derive(Clone, Debug, Default)]
+pub struct InputSummary {
+    pub lines: usize,
+    pub words: usize,
+    pub empty: usize,
+    pub wrong_length: usize,
+    pub repeated_letters: usize,
+    /// Line number, starting at 1, and content of every line with characters outside of the
+    /// alphabet.
+    pub invalid: Vec<(usize, String)>,
+}
+
+impl InputSummary {
+    pub fn rejected(&self) -> usize {
+        self.lines - self.words
+    }
+
+    pub(crate) fn record(&mut self, line: &str, result: Result<(), Rejection>) {
+        self.lines += 1;
+        match result {
+            Ok(()) => self.words += 1,
+            Err(Rejection::Empty) => self.empty += 1,
+            Err(Rejection::InvalidCharacter) => self.invalid.push((self.lines, line.to_owned())),
+            Err(Rejection::WrongLength) => self.wrong_length += 1,
+            Err(Rejection::RepeatedLetter) => self.repeated_letters += 1,
+        }
+    }
+
+    /// Adds the counts of the lines that follow the ones counted so far.
+    pub(crate) fn append(&mut self, other: InputSummary) {
+        let offset = self.lines;
+        self.lines += other.lines;
+        self.words += other.words;
+        self.empty += other.empty;
+        self.wrong_length += other.wrong_length;
+        self.repeated_letters += other.repeated_letters;
+        self.invalid.extend(
+            other
+                .invalid
+                .into_iter()
+                .map(|(line, text)| (offset + line, text)),
+        );
+    }
+}
+
+impl Display for InputSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} lines, {} words, {} rejected ({} empty, {} with invalid characters, {} of the wrong length, {} with repeated letters)",
+            self.lines,
+            self.words,
+            self.rejected(),
+            self.empty,
+            self.invalid.len(),
+            self.wrong_length,
+            self.repeated_letters
+        )
+    }
+}
+
+/// Trims surrounding whitespace, including the \r of CRLF line endings, case-folds the line and
+/// composes letters written with combining marks. With `strip_diacritics` accented letters that
+/// are not in the alphabet are replaced by their base letter, e.g. é by e. Lowercase ASCII
+/// lines, the bulk of most dictionaries, are borrowed as they are.
+pub(crate) fn normalize<'l>(
+    line: &'l str,
+    alphabet: &Alphabet,
+    strip_diacritics: bool,
+) -> Cow<'l, str> {
+    let line = line.trim();
+    if line.is_ascii() {
+        return match line.bytes().any(|b| b.is_ascii_uppercase()) {
+            true => Cow::Owned(line.to_ascii_lowercase()),
+            false => Cow::Borrowed(line),
+        };
+    }
+    let line = line.to_lowercase().nfc().collect::<String>();
+    if !strip_diacritics {
+        return Cow::Owned(line);
+    }
+    let mut stripped = String::with_capacity(line.len());
+    for c in line.chars() {
+        if alphabet.contains(c) {
+            stripped.push(c);
+        } else {
+            stripped.extend(std::iter::once(c).nfd().filter(|c| !is_combining_mark(*c)));
+        }
+    }
+    Cow::Owned(stripped)
+}
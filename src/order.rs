@@ -0,0 +1,119 @@
+use itertools::Itertools;
+
+use crate::alphabet::Alphabet;
+
+/// Decides the order in which the search covers the letters. Every anagram class is placed in
+/// the bucket of whichever of its letters comes first, and the search takes the first free
+/// letter in this order at every step, so letters with few classes should come early.
+pub trait LetterOrder: Send + Sync {
+    /// A short description for the [`RunReport`](crate::RunReport).
+    fn name(&self) -> String;
+
+    /// Every letter of the alphabet once, by index, in the order the search covers them.
+    /// `classes` holds the letters of every anagram class, with bit i set for the i-th letter.
+    fn order(&self, classes: &[u128], alphabet: &Alphabet) -> Vec<usize>;
+}
+
+/// Number of classes containing each letter.
+fn frequencies(classes: &[u128], alphabet_len: usize) -> Vec<usize> {
+    let mut freqs = vec![0; alphabet_len];
+    for bitword in classes {
+        for (letter, freq) in freqs.iter_mut().enumerate() {
+            if bitword & (1 << letter) != 0 {
+                *freq += 1;
+            }
+        }
+    }
+    freqs
+}
+
+/// The letter in the fewest classes first. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Frequency;
+
+impl LetterOrder for Frequency {
+    fn name(&self) -> String {
+        "frequency".to_owned()
+    }
+
+    fn order(&self, classes: &[u128], alphabet: &Alphabet) -> Vec<usize> {
+        frequencies(classes, alphabet.len())
+            .into_iter()
+            .enumerate()
+            .sorted_by_key(|(_letter, freq)| *freq)
+            .map(|(letter, _freq)| letter)
+            .collect()
+    }
+}
+
+/// Like [`Frequency`], but letters that are the rarest letter of no class go last, as they
+/// only ever share classes with rarer letters, and letters in equally many classes are ordered
+/// by how many classes they are the rarest letter of.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RarestLetter;
+
+impl LetterOrder for RarestLetter {
+    fn name(&self) -> String {
+        "rarest-letter".to_owned()
+    }
+
+    fn order(&self, classes: &[u128], alphabet: &Alphabet) -> Vec<usize> {
+        let freqs = frequencies(classes, alphabet.len());
+        let mut rarest = vec![0; alphabet.len()];
+        for bitword in classes {
+            let letter = (0..alphabet.len())
+                .filter(|letter| bitword & (1 << letter) != 0)
+                .min_by_key(|letter| freqs[*letter]);
+            if let Some(letter) = letter {
+                rarest[letter] += 1;
+            }
+        }
+        (0..alphabet.len())
+            .sorted_by_key(|letter| (rarest[*letter] == 0, freqs[*letter], rarest[*letter]))
+            .collect()
+    }
+}
+
+/// Picks the letter with the smallest bucket one at a time: the letter in the fewest classes
+/// first, then the letter in the fewest of the classes without the first letter, and so on.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BucketSize;
+
+impl LetterOrder for BucketSize {
+    fn name(&self) -> String {
+        "bucket-size".to_owned()
+    }
+
+    fn order(&self, classes: &[u128], alphabet: &Alphabet) -> Vec<usize> {
+        let mut classes = classes.to_vec();
+        let mut letters = (0..alphabet.len()).collect_vec();
+        let mut order = Vec::with_capacity(alphabet.len());
+        while !letters.is_empty() {
+            let freqs = frequencies(&classes, alphabet.len());
+            let (i, &letter) = letters
+                .iter()
+                .enumerate()
+                .min_by_key(|(_i, letter)| freqs[**letter])
+                .unwrap();
+            letters.remove(i);
+            order.push(letter);
+            classes.retain(|bitword| bitword & (1 << letter) == 0);
+        }
+        order
+    }
+}
+
+/// The letters in the given order, e.g. to try out an order found elsewhere.
+#[derive(Clone, Debug)]
+pub struct Explicit(pub String);
+
+impl LetterOrder for Explicit {
+    fn name(&self) -> String {
+        format!("explicit {}", self.0)
+    }
+
+    fn order(&self, _classes: &[u128], alphabet: &Alphabet) -> Vec<usize> {
+        let letters = self.0.chars().flat_map(char::to_lowercase);
+        letters.filter_map(|c| alphabet.index_of(c)).collect()
+    }
+}
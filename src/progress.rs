@@ -0,0 +1,102 @@
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// How far a run has got, passed to the callback set with
+/// [`SolverBuilder::progress`](crate::SolverBuilder::progress) whenever a root is done. The roots
+/// are the subtrees searched in parallel, one for every word covering the first letter or one
+/// of the letters after it when skipping.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    pub roots_done: usize,
+    pub roots: usize,
+    /// Solutions found so far, counting every combination of anagrams when expanded.
+    pub solutions: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// The fraction of the roots done, between 0 and 1.
+    pub fn fraction(&self) -> f64 {
+        match self.roots {
+            0 => 1.0,
+            roots => self.roots_done as f64 / roots as f64,
+        }
+    }
+
+    /// A guess at the time left, assuming the roots left take as long as the ones done so far on
+    /// average. Roots vary a lot in size, so it is rough.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.roots_done == 0 {
+            return None;
+        }
+        let left = self.roots - self.roots_done;
+        Some(self.elapsed.mul_f64(left as f64 / self.roots_done as f64))
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} roots ({:.1}%), {} solutions, {:.1} s",
+            self.roots_done,
+            self.roots,
+            self.fraction() * 100.0,
+            self.solutions,
+            self.elapsed.as_secs_f64()
+        )?;
+        match self.eta() {
+            Some(eta) => write!(f, ", about {:.1} s left", eta.as_secs_f64()),
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) type ProgressFn = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// Counts the roots done and the solutions found during a run, shared by all worker threads.
+pub(crate) struct Tracker {
+    callback: ProgressFn,
+    start: Instant,
+    roots: AtomicUsize,
+    roots_done: AtomicUsize,
+    solutions: AtomicU64,
+}
+
+impl Tracker {
+    pub(crate) fn new(callback: ProgressFn) -> Self {
+        Tracker {
+            callback,
+            start: Instant::now(),
+            roots: AtomicUsize::new(0),
+            roots_done: AtomicUsize::new(0),
+            solutions: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the number of roots once they are known.
+    pub(crate) fn start(&self, roots: usize) {
+        self.roots.store(roots, Ordering::Relaxed);
+    }
+
+    pub(crate) fn found(&self, solutions: u64) {
+        self.solutions.fetch_add(solutions, Ordering::Relaxed);
+    }
+
+    pub(crate) fn root_done(&self) {
+        // the last root done sees every solution found by the other threads
+        let roots_done = self.roots_done.fetch_add(1, Ordering::AcqRel) + 1;
+        (self.callback)(&Progress {
+            roots_done,
+            roots: self.roots.load(Ordering::Relaxed),
+            solutions: self.solutions.load(Ordering::Relaxed),
+            elapsed: self.start.elapsed(),
+        });
+    }
+}
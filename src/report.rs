@@ -0,0 +1,125 @@
+use std::{fmt::Display, time::Duration};
+
+use itertools::Itertools;
+
+use crate::{format::json_string, stats::SearchStats, stop::StopReason};
+
+/// How long each phase of a run took, along with the sizes of the dictionary and the search.
+/// The solver fills in the phases it runs; reading the dictionary and writing the output are up
+/// to the caller.
+#[derive(Clone, Debug, Default)]
+pub struct RunReport {
+    /// Reading the dictionary.
+    pub read: Duration,
+    /// Normalising and parsing the dictionary lines into words.
+    pub parse: Duration,
+    /// Grouping the words into anagram classes and bucketing them by letter.
+    pub index: Duration,
+    /// Splitting the top of the search tree into roots.
+    pub roots: Duration,
+    /// Searching the roots in parallel, including sorting or ranking the solutions.
+    pub search: Duration,
+    /// Flushing the output once the search is done. Most of it is written during the search.
+    pub write: Duration,
+    /// The [`LetterOrder`](crate::LetterOrder) of the search.
+    pub letter_order: String,
+    pub lines: usize,
+    pub words: usize,
+    pub classes: usize,
+    /// Number of anagram classes in the bucket of every letter, in the order the search covers
+    /// the letters.
+    pub buckets: Vec<(char, usize)>,
+    /// Number of roots searched in parallel.
+    pub root_count: usize,
+    /// Number of the roots that were skipped because they were done before resuming from a
+    /// [`Checkpoint`](crate::Checkpoint).
+    pub resumed: usize,
+    /// Number of solutions pushed to the sink, counting every combination of anagrams when
+    /// expanded.
+    pub solutions: u64,
+    /// Why the search ended early, if it did. The solutions found until then were pushed.
+    pub stopped: Option<StopReason>,
+    /// What the search did at every depth and letter, if the solver was
+    /// [`instrument`](crate::SolverBuilder::instrument)ed.
+    pub search_stats: Option<SearchStats>,
+}
+
+impl RunReport {
+    fn phases(&self) -> [(&'static str, Duration); 6] {
+        [
+            ("read", self.read),
+            ("parse", self.parse),
+            ("index", self.index),
+            ("roots", self.roots),
+            ("search", self.search),
+            ("write", self.write),
+        ]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases().iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// The report as a single JSON object, with durations in microseconds.
+    pub fn to_json(&self) -> String {
+        let phases = self
+            .phases()
+            .into_iter()
+            .chain([("total", self.total())])
+            .map(|(phase, duration)| format!(r#""{phase}_us":{}"#, duration.as_micros()));
+        let buckets = self
+            .buckets
+            .iter()
+            .map(|(letter, classes)| format!("[{},{classes}]", json_string(&letter.to_string())));
+        format!(
+            r#"{{{},"letter_order":{},"lines":{},"words":{},"classes":{},"roots":{},"resumed":{},"solutions":{},"stopped":{},"buckets":[{}],"search":{}}}"#,
+            phases.format(","),
+            json_string(&self.letter_order),
+            self.lines,
+            self.words,
+            self.classes,
+            self.root_count,
+            self.resumed,
+            self.solutions,
+            self.stopped
+                .map_or("null".to_owned(), |reason| json_string(reason.name())),
+            buckets.format(","),
+            self.search_stats
+                .as_ref()
+                .map_or("null".to_owned(), SearchStats::to_json)
+        )
+    }
+}
+
+impl Display for RunReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (phase, duration) in self.phases().into_iter().chain([("total", self.total())]) {
+            writeln!(f, "{phase:<7}{:>12.3} ms", duration.as_secs_f64() * 1e3)?;
+        }
+        writeln!(
+            f,
+            "{} lines, {} words, {} anagram classes, {} roots, {} solutions",
+            self.lines, self.words, self.classes, self.root_count, self.solutions
+        )?;
+        if self.resumed > 0 {
+            writeln!(f, "{} roots resumed from the checkpoint", self.resumed)?;
+        }
+        if let Some(reason) = self.stopped {
+            writeln!(f, "stopped early: {reason}")?;
+        }
+        let buckets = self
+            .buckets
+            .iter()
+            .map(|(letter, classes)| format!("{letter} {classes}"));
+        write!(
+            f,
+            "classes by letter in {} order: {}",
+            self.letter_order,
+            buckets.format(", ")
+        )?;
+        if let Some(stats) = &self.search_stats {
+            write!(f, "\n{}", stats.to_string().trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,673 @@
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    sync::{
+        atomic::{self, AtomicUsize},
+        Mutex,
+    },
+    time::Instant,
+};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+
+use crate::{
+    alphabet::Alphabet,
+    bitword::Bitword,
+    checkpoint::{Collapsed, Log},
+    frequency::Score,
+    index::{next_free_letter, AnagramClass, WordIndex},
+    progress::Tracker,
+    report::RunReport,
+    shard::Shard,
+    sink::{SolutionSink, VecSink},
+    solution::Solution,
+    solver::Puzzle,
+    stats::{Counters, TreeCounts},
+    stop::Stop,
+    word::Word,
+};
+
+/// A first word to search from, after skipping some of the most significant free letters.
+pub(crate) struct Root<'a, B> {
+    /// The first word, or none if the required words alone make up a solution.
+    pub(crate) class: Option<&'a AnagramClass<B>>,
+    /// The letters of the required words, the unavailable letters and the skipped letters.
+    filter: B,
+    /// What is left of the skip budget.
+    skips: usize,
+}
+
+/// A single run of the solver over a word index.
+pub(crate) struct Search<'a, B> {
+    pub(crate) index: &'a WordIndex<B>,
+    pub(crate) alphabet: &'a Alphabet,
+    pub(crate) puzzle: Puzzle,
+    pub(crate) expand_anagrams: bool,
+    pub(crate) sorted: bool,
+    /// Emit the solutions best first by this score instead, keeping only the `top` ones.
+    pub(crate) rank: Option<Score>,
+    pub(crate) top: Option<usize>,
+    /// Count what the search does at every depth and letter.
+    pub(crate) instrument: bool,
+    /// Transformed letters no word may use. They are filtered out from the start and count
+    /// against the skip budget.
+    pub(crate) unavailable: B,
+    /// Words every solution contains, placed before the search starts.
+    pub(crate) required: Vec<&'a [Word]>,
+    /// Transformed letters of the required words.
+    pub(crate) required_bitword: B,
+    /// When to end the search before it is done.
+    pub(crate) stop: Stop,
+    /// Where to report the roots done, if anywhere.
+    pub(crate) progress: Option<Tracker>,
+    /// Only search these roots.
+    pub(crate) shard: Option<Shard>,
+    /// Where to write the roots done, if anywhere. Only unsorted runs are checkpointed.
+    pub(crate) checkpoint: Option<Log<'a>>,
+}
+
+impl<'a, B: Bitword> Search<'a, B> {
+    /// Pushes every solution into `sink`, timing the phases in `report`.
+    pub(crate) fn run<S>(&self, sink: &S, report: &mut RunReport)
+    where
+        S: SolutionSink<'a>,
+    {
+        if !self.instrument {
+            return self.run_counted::<(), S>(sink, report);
+        }
+        let counters = self.run_counted::<TreeCounts, S>(sink, report);
+        report.search_stats = Some(counters.into_stats(|letter| {
+            let letter = self.index.restore(B::bit(letter)).trailing_zeros();
+            self.alphabet.letter(letter as usize)
+        }));
+    }
+
+    fn run_counted<C, S>(&self, sink: &S, report: &mut RunReport) -> C
+    where
+        C: Counters,
+        S: SolutionSink<'a>,
+    {
+        let start = Instant::now();
+        let mut counters: C = self.counters();
+        let roots = self.roots(&mut counters);
+        report.roots = start.elapsed();
+        report.root_count = roots.len();
+        self.start_progress(roots.len());
+
+        let start = Instant::now();
+        let counters = counters.merge(match (self.rank, self.sorted) {
+            (Some(score), _) => self.run_ranked(&roots, score, sink),
+            (None, true) => self.run_sorted(&roots, sink),
+            (None, false) => self.run_unsorted(&roots, sink),
+        });
+        report.search = start.elapsed();
+        report.stopped = self.stop.reason();
+        counters
+    }
+
+    fn start_progress(&self, roots: usize) {
+        if let Some(progress) = &self.progress {
+            progress.start(roots);
+        }
+    }
+
+    fn root_done(&self) {
+        if let Some(progress) = &self.progress {
+            progress.root_done();
+        }
+    }
+
+    fn counters<C: Counters>(&self) -> C {
+        C::new(self.puzzle.max_word_count() + 1, self.index.len())
+    }
+
+    fn run_unsorted<C, S>(&self, roots: &[Root<'a, B>], sink: &S) -> C
+    where
+        C: Counters,
+        S: SolutionSink<'a>,
+    {
+        roots
+            .par_iter()
+            .enumerate()
+            .fold(
+                || (sink.buffer(), self.counters()),
+                |(mut buffer, mut counters), (i, root)| {
+                    match &self.checkpoint {
+                        Some(log) => {
+                            self.solve_logged(log, i, sink, &mut buffer, &mut counters, root)
+                        }
+                        None => self.solve_root(sink, &mut buffer, &mut counters, root),
+                    }
+                    self.root_done();
+                    (buffer, counters)
+                },
+            )
+            .map(|(buffer, counters)| {
+                sink.flush(buffer);
+                counters
+            })
+            .reduce(|| self.counters(), C::merge)
+    }
+
+    /// Every solution below `roots` in canonical form.
+    fn collect<C: Counters>(&self, roots: &[Root<'a, B>]) -> (Vec<Solution<'a>>, C) {
+        // collect the solutions of every root separately and merge them in root order, so the
+        // result does not depend on the thread scheduling
+        let collector = VecSink::new();
+        let (buffers, counters): (Vec<_>, Vec<C>) = roots
+            .par_iter()
+            .map(|root| {
+                let mut buffer = collector.buffer();
+                let mut counters = self.counters();
+                self.solve_root(&collector, &mut buffer, &mut counters, root);
+                self.root_done();
+                (buffer, counters)
+            })
+            .unzip();
+        let mut solutions = buffers.into_iter().flatten().collect_vec();
+        solutions.par_iter_mut().for_each(Solution::canonicalize);
+        let counters = counters.into_iter().fold(self.counters(), C::merge);
+        (solutions, counters)
+    }
+
+    fn run_sorted<C, S>(&self, roots: &[Root<'a, B>], sink: &S) -> C
+    where
+        C: Counters,
+        S: SolutionSink<'a>,
+    {
+        let (mut solutions, counters) = self.collect(roots);
+        solutions.par_sort_unstable();
+
+        let mut buffer = sink.buffer();
+        for solution in &solutions {
+            sink.push(&mut buffer, solution);
+        }
+        sink.flush(buffer);
+        counters
+    }
+
+    fn run_ranked<C, S>(&self, roots: &[Root<'a, B>], score: Score, sink: &S) -> C
+    where
+        C: Counters,
+        S: SolutionSink<'a>,
+    {
+        let (solutions, counters) = self.collect(roots);
+        let mut solutions = solutions
+            .into_par_iter()
+            .map(|solution| (solution.score(score), solution))
+            .collect::<Vec<_>>();
+        // best score first, ties in canonical order
+        let order = |(a_score, a): &(f64, Solution), (b_score, b): &(f64, Solution)| {
+            b_score.total_cmp(a_score).then_with(|| a.cmp(b))
+        };
+        if let Some(top) = self.top.filter(|top| *top < solutions.len()) {
+            solutions.select_nth_unstable_by(top, order);
+            solutions.truncate(top);
+        }
+        solutions.par_sort_unstable_by(order);
+
+        let mut buffer = sink.buffer();
+        for (_, solution) in &solutions {
+            sink.push(&mut buffer, solution);
+        }
+        sink.flush(buffer);
+        counters
+    }
+
+    fn required_len(&self) -> usize {
+        self.required.iter().map(|words| words[0].len()).sum()
+    }
+
+    /// The top of the search tree, split up so the subtrees can be searched in parallel.
+    fn roots<C: Counters>(&self, counters: &mut C) -> Vec<Root<'a, B>> {
+        let roots = match self.puzzle.unused.checked_sub(self.unavailable.count()) {
+            Some(unused) => self.roots_within(unused, counters),
+            None => Vec::new(),
+        };
+        match self.shard {
+            Some(shard) => roots
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _root)| shard.contains(*i))
+                .map(|(_i, root)| root)
+                .collect(),
+            None => roots,
+        }
+    }
+
+    /// The roots when at most `unused` more letters may be skipped.
+    fn roots_within<C: Counters>(&self, unused: usize, counters: &mut C) -> Vec<Root<'a, B>> {
+        let required_len = self.required_len();
+        let mut filter = self.required_bitword | self.unavailable;
+        if required_len >= self.puzzle.letters {
+            let root = Root {
+                class: None,
+                filter,
+                skips: unused,
+            };
+            return match required_len == self.puzzle.letters {
+                true => vec![root],
+                false => Vec::new(),
+            };
+        }
+
+        // the most significant free letter is either covered by the first word or skipped, in
+        // which case the next free letter must be covered or skipped, and so on until the skip
+        // budget runs out
+        let mut roots = Vec::new();
+        for skips in (0..=unused).rev() {
+            let Some(letter) = next_free_letter(filter, self.index.len()) else {
+                break;
+            };
+            let before = roots.len();
+            roots.extend(
+                self.index[letter]
+                    .iter()
+                    .filter(|class| class.bitword & filter == B::ZERO)
+                    .filter(|class| required_len + class.len <= self.puzzle.letters)
+                    .map(|class| Root {
+                        class: Some(class),
+                        filter,
+                        skips,
+                    }),
+            );
+            if let Some(counts) = counters.at(0, letter) {
+                counts.nodes += 1;
+                counts.tested += self.index[letter].len() as u64;
+                counts.extended += (roots.len() - before) as u64;
+                counts.skipped += (skips > 0) as u64;
+            }
+            filter = filter | B::bit(letter);
+        }
+        roots
+    }
+
+    /// Searches every solution below `root`.
+    fn solve_root<C, S>(
+        &self,
+        sink: &S,
+        buffer: &mut S::Buffer,
+        counters: &mut C,
+        root: &Root<'a, B>,
+    ) where
+        C: Counters,
+        S: SolutionSink<'a>,
+    {
+        let mut solution = Vec::with_capacity(self.puzzle.max_word_count());
+        let mut filter = root.filter;
+        let mut covered = self.required_len();
+        if let Some(class) = root.class {
+            solution.push(class);
+            filter = filter | class.bitword;
+            covered += class.len;
+        }
+        let skips = root.skips;
+        self.solve14(
+            sink,
+            buffer,
+            counters,
+            filter,
+            skips,
+            covered,
+            &mut solution,
+        );
+    }
+
+    /// Searches the root at position `i` unless it was done before resuming, and writes it to
+    /// the log with its solutions unless the search was stopped on the way.
+    fn solve_logged<C, S>(
+        &self,
+        log: &Log,
+        i: usize,
+        sink: &S,
+        buffer: &mut S::Buffer,
+        counters: &mut C,
+        root: &Root<'a, B>,
+    ) where
+        C: Counters,
+        S: SolutionSink<'a>,
+    {
+        if log.is_done(i) {
+            return;
+        }
+        let mut solutions = Collapsed.buffer();
+        self.solve_root(&Collapsed, &mut solutions, counters, root);
+        if !self.stop.is_stopped() {
+            log.record(i, &solutions);
+        }
+        for solution in &solutions {
+            match self.expand_anagrams {
+                true => sink.push_expanded(buffer, solution),
+                false => sink.push(buffer, solution),
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn solve14<C, S>(
+        &self,
+        sink: &S,
+        buffer: &mut S::Buffer,
+        counters: &mut C,
+        filter: B,
+        skips: usize,
+        covered: usize,
+        solution: &mut Vec<&'a AnagramClass<B>>,
+    ) where
+        C: Counters,
+        S: SolutionSink<'a>,
+    {
+        let depth = solution.len();
+        if self.stopped(depth) {
+            return;
+        }
+        let word_count = self.puzzle.word_count;
+        if covered == self.puzzle.letters {
+            let len = self.required.len() + solution.len();
+            if word_count.is_some_and(|word_count| word_count != len) {
+                return;
+            }
+            self.push(sink, buffer, &self.solution(solution));
+            return;
+        }
+        if word_count == Some(self.required.len() + solution.len()) {
+            return;
+        }
+        let letter = next_free_letter(filter, self.index.len()).unwrap();
+        if let Some(counts) = counters.at(depth, letter) {
+            counts.nodes += 1;
+            counts.tested += self.index[letter].len() as u64;
+        }
+        for class in &self.index[letter] {
+            if class.bitword & filter == B::ZERO && covered + class.len <= self.puzzle.letters {
+                if let Some(counts) = counters.at(depth, letter) {
+                    counts.extended += 1;
+                }
+                solution.push(class);
+                let filter = filter | class.bitword;
+                let covered = covered + class.len;
+                self.solve14(sink, buffer, counters, filter, skips, covered, solution);
+                solution.pop();
+            }
+        }
+        if skips > 0 {
+            if let Some(counts) = counters.at(depth, letter) {
+                counts.skipped += 1;
+            }
+            let filter = filter | B::bit(letter);
+            self.solve14(sink, buffer, counters, filter, skips - 1, covered, solution);
+        }
+    }
+
+    /// Whether to stop searching at a node `depth` words down. Only the nodes near the top of the
+    /// tree look at the clock and the cancellation token, which is often enough and cheap.
+    #[inline]
+    fn stopped(&self, depth: usize) -> bool {
+        match depth < 3 {
+            true => self.stop.check(),
+            false => self.stop.is_stopped(),
+        }
+    }
+
+    /// Pushes `solution`, or as many of its expansions as the limit allows.
+    pub(crate) fn push<S: SolutionSink<'a>>(
+        &self,
+        sink: &S,
+        buffer: &mut S::Buffer,
+        solution: &Solution<'a>,
+    ) {
+        let combinations = match self.expand_anagrams {
+            true => solution.combinations(),
+            false => 1,
+        };
+        let n = self.stop.take(combinations);
+        if let Some(progress) = &self.progress {
+            progress.found(n);
+        }
+        if !self.expand_anagrams {
+            if n == 1 {
+                sink.push(buffer, solution);
+            }
+        } else if n == combinations {
+            sink.push_expanded(buffer, solution);
+        } else {
+            for solution in solution.expand().take(n as usize) {
+                sink.push(buffer, &solution);
+            }
+        }
+    }
+
+    /// The solution made of the required words and `classes`.
+    fn solution(&self, classes: &[&'a AnagramClass<B>]) -> Solution<'a> {
+        let covered = classes
+            .iter()
+            .fold(self.required_bitword, |covered, class| {
+                covered | class.bitword
+            });
+        let missing = self.index.restore(!covered & B::mask(0..self.index.len()));
+        let anagrams = self
+            .required
+            .iter()
+            .copied()
+            .chain(classes.iter().map(|class| &class.words[..]))
+            .collect();
+        Solution::new(anagrams, missing, self.alphabet)
+    }
+
+    /// Pushes the `limit` combinations of words scoring best, best first. Unlike
+    /// [`run`](Self::run), any number of letters may be skipped and a combination may have fewer
+    /// words than the puzzle asks for, so there is something to show when no combination covers
+    /// all of the letters. The words are disjoint except for the last one, which may share
+    /// letters with the others, and a combination scores the letters it covers minus the letters
+    /// shared. The subtrees that cannot beat the worst of the best combinations found so far are
+    /// cut off.
+    pub(crate) fn run_best<S>(&self, limit: usize, sink: &S, report: &mut RunReport)
+    where
+        S: SolutionSink<'a>,
+    {
+        let start = Instant::now();
+        let roots = match limit {
+            0 => Vec::new(),
+            _ => self.roots_within(self.index.len(), &mut ()),
+        };
+        report.roots = start.elapsed();
+        report.root_count = roots.len();
+        // every root is searched twice
+        self.start_progress(2 * roots.len());
+
+        let start = Instant::now();
+        let best = Best {
+            limit,
+            covers: Mutex::new(BinaryHeap::with_capacity(limit + 1)),
+            threshold: AtomicUsize::new(0),
+        };
+        // the disjoint combinations come first, so the threshold is high by the time the
+        // search for a last word sharing letters starts and most of the tree is cut off
+        for shared in [false, true] {
+            roots.par_iter().for_each(|root| {
+                self.best_root(&best, root, shared);
+                self.root_done();
+            });
+        }
+
+        let mut buffer = sink.buffer();
+        let covers = best.covers.into_inner().unwrap().into_sorted_vec();
+        for cover in &covers {
+            self.push(sink, &mut buffer, &cover.solution);
+        }
+        sink.flush(buffer);
+        report.search = start.elapsed();
+        report.stopped = self.stop.reason();
+    }
+
+    /// Offers the combinations below `root`: the disjoint ones, or with `shared` the ones whose
+    /// last word shares letters with the others.
+    fn best_root(&self, best: &Best<'a>, root: &Root<'a, B>, shared: bool) {
+        let mut solution = Vec::with_capacity(self.puzzle.max_word_count());
+        let mut filter = root.filter;
+        let mut covered = self.required_len();
+        if let Some(class) = root.class {
+            solution.push(class);
+            filter = filter | class.bitword;
+            covered += class.len;
+        }
+        self.offer_best(best, covered, &mut solution, shared);
+        self.best_cover(best, filter, covered, &mut solution, shared);
+    }
+
+    fn best_cover(
+        &self,
+        best: &Best<'a>,
+        filter: B,
+        covered: usize,
+        solution: &mut Vec<&'a AnagramClass<B>>,
+        shared: bool,
+    ) {
+        let words = self.required.len() + solution.len();
+        let words_left = self.puzzle.max_word_count().saturating_sub(words);
+        if words_left == 0 {
+            return;
+        }
+        // bound the score of this subtree by the free letters and the words left
+        let max_len = self.puzzle.max_word_len();
+        let free = self.index.len() - filter.count();
+        let bound = match shared {
+            false => (covered + free.min(words_left * max_len)).min(self.puzzle.letters),
+            // the last word may cover skipped letters, but it shares at least one letter and
+            // scores at most its length minus twice the letters shared
+            true => (covered + free.min((words_left - 1) * max_len) + max_len.saturating_sub(2))
+                .min(self.index.len() - self.unavailable.count() - 1)
+                .min(self.puzzle.letters - 1),
+        };
+        if bound < best.threshold() {
+            return;
+        }
+        if self.stopped(solution.len()) {
+            return;
+        }
+        let Some(letter) = next_free_letter(filter, self.index.len()) else {
+            return;
+        };
+        for class in &self.index[letter] {
+            if class.bitword & filter == B::ZERO && covered + class.len <= self.puzzle.letters {
+                solution.push(class);
+                let covered = covered + class.len;
+                self.offer_best(best, covered, solution, shared);
+                self.best_cover(best, filter | class.bitword, covered, solution, shared);
+                solution.pop();
+            }
+        }
+        let filter = filter | B::bit(letter);
+        self.best_cover(best, filter, covered, solution, shared);
+    }
+
+    /// Offers the disjoint combination of the required words and `solution`, or with `shared`
+    /// the combinations of them with a last word sharing letters, if there is room for it.
+    fn offer_best(
+        &self,
+        best: &Best<'a>,
+        covered: usize,
+        solution: &mut Vec<&'a AnagramClass<B>>,
+        shared: bool,
+    ) {
+        if !shared {
+            return best.offer(covered, || self.solution(solution));
+        }
+        // a last word sharing n letters adds at most its length minus n letters and costs n
+        let words = self.required.len() + solution.len();
+        if words >= self.puzzle.max_word_count()
+            || covered + self.puzzle.max_word_len() < best.threshold() + 2
+        {
+            return;
+        }
+        let letters = solution
+            .iter()
+            .fold(self.required_bitword, |letters, class| {
+                letters | class.bitword
+            });
+        for class in self.index.classes() {
+            let common = (class.bitword & letters).count();
+            let added = class.len - common;
+            // sharing as many letters as it adds, the word is no better than leaving it out
+            if common == 0
+                || added <= common
+                || covered + added > self.puzzle.letters
+                || class.bitword & self.unavailable != B::ZERO
+            {
+                continue;
+            }
+            solution.push(class);
+            best.offer(covered + added - common, || self.solution(solution));
+            solution.pop();
+        }
+    }
+}
+
+/// The best combinations found so far by [`Search::run_best`].
+struct Best<'a> {
+    limit: usize,
+    /// The worst combination is on top.
+    covers: Mutex<BinaryHeap<Cover<'a>>>,
+    /// The score of the worst combination once there are `limit` of them.
+    threshold: AtomicUsize,
+}
+
+impl<'a> Best<'a> {
+    fn threshold(&self) -> usize {
+        self.threshold.load(atomic::Ordering::Relaxed)
+    }
+
+    fn offer(&self, score: usize, solution: impl FnOnce() -> Solution<'a>) {
+        if score < self.threshold() {
+            return;
+        }
+        let mut solution = solution();
+        solution.canonicalize();
+        let mut covers = self.covers.lock().unwrap();
+        // words sharing letters are found once for every word that can be left out to make
+        // the others disjoint
+        if covers.iter().any(|cover| cover.solution == solution) {
+            return;
+        }
+        covers.push(Cover { score, solution });
+        if covers.len() > self.limit {
+            covers.pop();
+        }
+        if covers.len() == self.limit {
+            let worst = covers.peek().unwrap().score;
+            self.threshold.store(worst, atomic::Ordering::Relaxed);
+        }
+    }
+}
+
+/// A combination of words, ordered from best to worst by its score, ties broken by the
+/// canonical order of the solutions.
+struct Cover<'a> {
+    /// The letters covered minus the letters shared by two words.
+    score: usize,
+    solution: Solution<'a>,
+}
+
+impl Ord for Cover<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .cmp(&self.score)
+            .then_with(|| self.solution.cmp(&other.solution))
+    }
+}
+
+impl PartialOrd for Cover<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Cover<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Cover<'_> {}
@@ -0,0 +1,143 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::error::Error;
+
+/// A slice of the roots of the search tree, for splitting a run across processes or machines.
+/// The roots are numbered in a deterministic order and dealt out in turn, so shard i of n
+/// searches roots i-1, i-1+n, i-1+2n and so on, which spreads the large roots over the shards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shard {
+    index: usize,
+    count: usize,
+}
+
+impl Shard {
+    /// The `index`-th of `count` shards, counting from 1.
+    pub fn new(index: usize, count: usize) -> Result<Self, Error> {
+        if index == 0 || index > count {
+            return Err(Error::InvalidShard(format!(
+                "shard {index} of {count} is not between 1 and {count}"
+            )));
+        }
+        Ok(Shard { index, count })
+    }
+
+    pub fn index(self) -> usize {
+        self.index
+    }
+
+    pub fn count(self) -> usize {
+        self.count
+    }
+
+    /// Whether the root at `root` in the order of the roots belongs to this shard.
+    pub(crate) fn contains(self, root: usize) -> bool {
+        root % self.count == self.index - 1
+    }
+}
+
+impl FromStr for Shard {
+    type Err = Error;
+
+    /// Parses `i/n`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidShard(format!("{s:?} is not of the form i/n"));
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index = index.trim().parse().map_err(|_| invalid())?;
+        let count = count.trim().parse().map_err(|_| invalid())?;
+        Shard::new(index, count)
+    }
+}
+
+impl Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// What a shard run records next to its output, so the shards can be checked before merging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShardManifest {
+    pub shard: Shard,
+    /// The [`Solver::fingerprint`](crate::Solver::fingerprint) of the run.
+    pub fingerprint: u64,
+    /// Number of solutions written.
+    pub solutions: u64,
+    /// Whether every root of the shard was searched, i.e. the run was not stopped early.
+    pub complete: bool,
+}
+
+impl ShardManifest {
+    /// Parses the lines written by the `Display` implementation.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let (mut shard, mut fingerprint, mut solutions, mut complete) = (None, None, None, None);
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let invalid = || Error::InvalidShard(format!("{line:?} is not a manifest line"));
+            let (key, value) = line.trim().split_once(' ').ok_or_else(invalid)?;
+            match key {
+                "shard" => shard = Some(value.parse()?),
+                "fingerprint" => {
+                    fingerprint = Some(u64::from_str_radix(value, 16).map_err(|_| invalid())?)
+                }
+                "solutions" => solutions = Some(value.parse().map_err(|_| invalid())?),
+                "complete" => complete = Some(value.parse().map_err(|_| invalid())?),
+                _ => return Err(invalid()),
+            }
+        }
+        let missing = |key| Error::InvalidShard(format!("the manifest has no {key} line"));
+        Ok(ShardManifest {
+            shard: shard.ok_or_else(|| missing("shard"))?,
+            fingerprint: fingerprint.ok_or_else(|| missing("fingerprint"))?,
+            solutions: solutions.ok_or_else(|| missing("solutions"))?,
+            complete: complete.ok_or_else(|| missing("complete"))?,
+        })
+    }
+}
+
+impl Display for ShardManifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "shard {}", self.shard)?;
+        writeln!(f, "fingerprint {:016x}", self.fingerprint)?;
+        writeln!(f, "solutions {}", self.solutions)?;
+        writeln!(f, "complete {}", self.complete)
+    }
+}
+
+/// 64-bit FNV-1a over values written in a fixed layout: integers as little-endian bytes and
+/// strings as their length followed by their UTF-8 bytes. Unlike the hasher of the standard
+/// library and the `Hash` implementations feeding it, the layout does not depend on the
+/// platform or the Rust version, so fingerprints can be compared across machines.
+pub(crate) struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Writes `n` as 8 bytes whatever the size of `usize`.
+    pub(crate) fn write_usize(&mut self, n: usize) {
+        self.write(&(n as u64).to_le_bytes());
+    }
+
+    pub(crate) fn write_u128(&mut self, n: u128) {
+        self.write(&n.to_le_bytes());
+    }
+
+    pub(crate) fn write_str(&mut self, s: &str) {
+        self.write_usize(s.len());
+        self.write(s.as_bytes());
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
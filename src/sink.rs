@@ -0,0 +1,280 @@
+use std::{
+    io::{self, Write},
+    mem,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::Sender,
+        Mutex,
+    },
+};
+
+use crate::{format::Format, solution::Solution};
+
+/// Receives the solutions found by [`Solver::run`](crate::Solver::run).
+///
+/// Every worker thread pushes into its own buffer and hands it back through `flush` when it is
+/// done, so sinks only have to synchronise once per buffer instead of once per solution.
+pub trait SolutionSink<'a>: Sync {
+    type Buffer: Send;
+
+    fn buffer(&self) -> Self::Buffer;
+
+    fn push(&self, buffer: &mut Self::Buffer, solution: &Solution<'a>);
+
+    /// Pushes every combination of anagrams in `solution`. Sinks that do not need the words
+    /// themselves can override this to avoid the expansion.
+    fn push_expanded(&self, buffer: &mut Self::Buffer, solution: &Solution<'a>) {
+        for solution in solution.expand() {
+            self.push(buffer, &solution);
+        }
+    }
+
+    fn flush(&self, buffer: Self::Buffer);
+}
+
+/// Writes one solution per line in the given [`Format`]. Lines are written in chunks from each
+/// thread, so solutions from different threads are interleaved in no particular order.
+pub struct WriterSink<W> {
+    writer: Mutex<W>,
+    format: Format,
+    word_count: usize,
+    error: Mutex<Option<io::Error>>,
+}
+
+impl<W: Write + Send> WriterSink<W> {
+    const CHUNK_SIZE: usize = 1 << 16;
+
+    pub fn new(writer: W) -> Self {
+        WriterSink {
+            writer: Mutex::new(writer),
+            format: Format::Text,
+            word_count: 0,
+            error: Mutex::new(None),
+        }
+    }
+
+    /// Writes the header of `format`, if any, for solutions of up to `word_count` words.
+    pub fn with_format(mut writer: W, format: Format, word_count: usize) -> io::Result<Self> {
+        format.write_header(&mut writer, word_count)?;
+        Ok(WriterSink {
+            writer: Mutex::new(writer),
+            format,
+            word_count,
+            error: Mutex::new(None),
+        })
+    }
+
+    fn write(&self, bytes: &[u8]) {
+        if let Err(e) = self.writer.lock().unwrap().write_all(bytes) {
+            self.error.lock().unwrap().get_or_insert(e);
+        }
+    }
+
+    /// Flushes the writer and returns it, or the first error encountered while writing.
+    pub fn finish(self) -> io::Result<W> {
+        if let Some(e) = self.error.into_inner().unwrap() {
+            return Err(e);
+        }
+        let mut writer = self.writer.into_inner().unwrap();
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+impl<'a, W: Write + Send> SolutionSink<'a> for WriterSink<W> {
+    type Buffer = Vec<u8>;
+
+    fn buffer(&self) -> Self::Buffer {
+        Vec::with_capacity(Self::CHUNK_SIZE)
+    }
+
+    fn push(&self, buffer: &mut Self::Buffer, solution: &Solution<'a>) {
+        self.format
+            .write(buffer, solution, self.word_count)
+            .unwrap();
+        if buffer.len() >= Self::CHUNK_SIZE {
+            self.write(buffer);
+            buffer.clear();
+        }
+    }
+
+    fn flush(&self, buffer: Self::Buffer) {
+        self.write(&buffer);
+    }
+}
+
+/// Collects the solutions in memory.
+#[derive(Default)]
+pub struct VecSink<'a> {
+    solutions: Mutex<Vec<Solution<'a>>>,
+}
+
+impl<'a> VecSink<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_inner(self) -> Vec<Solution<'a>> {
+        self.solutions.into_inner().unwrap()
+    }
+}
+
+impl<'a> SolutionSink<'a> for VecSink<'a> {
+    type Buffer = Vec<Solution<'a>>;
+
+    fn buffer(&self) -> Self::Buffer {
+        Vec::new()
+    }
+
+    fn push(&self, buffer: &mut Self::Buffer, solution: &Solution<'a>) {
+        buffer.push(solution.clone());
+    }
+
+    fn flush(&self, mut buffer: Self::Buffer) {
+        self.solutions.lock().unwrap().append(&mut buffer);
+    }
+}
+
+/// Counts the solutions without formatting or keeping them. When anagrams are expanded, the
+/// combinations of every class-level solution are counted without being materialised.
+#[derive(Default)]
+pub struct CountSink {
+    solutions: AtomicU64,
+    class_solutions: AtomicU64,
+}
+
+impl CountSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of solutions pushed, counting every combination of anagrams when expanded.
+    pub fn count(&self) -> u64 {
+        self.solutions.load(Ordering::Relaxed)
+    }
+
+    /// Number of solutions up to anagrams.
+    pub fn class_count(&self) -> u64 {
+        self.class_solutions.load(Ordering::Relaxed)
+    }
+}
+
+impl SolutionSink<'_> for CountSink {
+    /// Solutions and class-level solutions counted by one thread.
+    type Buffer = (u64, u64);
+
+    fn buffer(&self) -> Self::Buffer {
+        (0, 0)
+    }
+
+    fn push(&self, buffer: &mut Self::Buffer, _solution: &Solution) {
+        buffer.0 += 1;
+        buffer.1 += 1;
+    }
+
+    fn push_expanded(&self, buffer: &mut Self::Buffer, solution: &Solution) {
+        buffer.0 += solution.combinations();
+        buffer.1 += 1;
+    }
+
+    fn flush(&self, (solutions, class_solutions): Self::Buffer) {
+        self.solutions.fetch_add(solutions, Ordering::Relaxed);
+        self.class_solutions
+            .fetch_add(class_solutions, Ordering::Relaxed);
+    }
+}
+
+/// Sends the solutions to a channel in batches of `batch_size`. The receiver has to be drained
+/// from another thread while the solver runs, e.g. inside [`std::thread::scope`]. Solutions are
+/// dropped once the receiver hangs up.
+pub struct ChannelSink<'a> {
+    sender: Sender<Vec<Solution<'a>>>,
+    batch_size: usize,
+}
+
+impl<'a> ChannelSink<'a> {
+    pub fn new(sender: Sender<Vec<Solution<'a>>>, batch_size: usize) -> Self {
+        ChannelSink {
+            sender,
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+impl<'a> SolutionSink<'a> for ChannelSink<'a> {
+    type Buffer = Vec<Solution<'a>>;
+
+    fn buffer(&self) -> Self::Buffer {
+        Vec::with_capacity(self.batch_size)
+    }
+
+    fn push(&self, buffer: &mut Self::Buffer, solution: &Solution<'a>) {
+        buffer.push(solution.clone());
+        if buffer.len() >= self.batch_size {
+            self.flush(mem::replace(buffer, self.buffer()));
+        }
+    }
+
+    fn flush(&self, buffer: Self::Buffer) {
+        if !buffer.is_empty() {
+            let _ = self.sender.send(buffer);
+        }
+    }
+}
+
+/// Calls a closure for every solution, used by [`Solver::for_each`](crate::Solver::for_each).
+pub(crate) struct FnSink<F>(pub(crate) F);
+
+impl<'a, F: Fn(&Solution<'a>) + Sync> SolutionSink<'a> for FnSink<F> {
+    type Buffer = ();
+
+    fn buffer(&self) -> Self::Buffer {}
+
+    fn push(&self, _buffer: &mut Self::Buffer, solution: &Solution<'a>) {
+        (self.0)(solution)
+    }
+
+    fn flush(&self, _buffer: Self::Buffer) {}
+}
+
+/// Counts the solutions on their way to another sink, for the [`RunReport`](crate::RunReport).
+pub(crate) struct Counted<'s, S> {
+    sink: &'s S,
+    solutions: AtomicU64,
+}
+
+impl<'s, S> Counted<'s, S> {
+    pub(crate) fn new(sink: &'s S) -> Self {
+        Counted {
+            sink,
+            solutions: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.solutions.load(Ordering::Relaxed)
+    }
+}
+
+impl<'a, S: SolutionSink<'a>> SolutionSink<'a> for Counted<'_, S> {
+    type Buffer = (S::Buffer, u64);
+
+    fn buffer(&self) -> Self::Buffer {
+        (self.sink.buffer(), 0)
+    }
+
+    fn push(&self, (buffer, count): &mut Self::Buffer, solution: &Solution<'a>) {
+        *count += 1;
+        self.sink.push(buffer, solution);
+    }
+
+    fn push_expanded(&self, (buffer, count): &mut Self::Buffer, solution: &Solution<'a>) {
+        *count += solution.combinations();
+        self.sink.push_expanded(buffer, solution);
+    }
+
+    fn flush(&self, (buffer, count): Self::Buffer) {
+        self.solutions.fetch_add(count, Ordering::Relaxed);
+        self.sink.flush(buffer);
+    }
+}
@@ -0,0 +1,104 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use itertools::Itertools;
+
+use crate::{alphabet::Alphabet, frequency::Score, word::Word};
+
+/// A set of letter-disjoint words. Each position holds every anagram that fits there, so a
+/// collapsed solution stands for all combinations of its anagrams.
+///
+/// Solutions are ordered lexicographically by their positions, which is only meaningful for
+/// solutions in canonical order.
+#[derive(Clone, Debug)]
+pub struct Solution<'a> {
+    anagrams: Vec<&'a [Word]>,
+    /// Bit i is set if the i-th letter of the alphabet is not covered.
+    missing: u128,
+    alphabet: &'a Alphabet,
+}
+
+impl<'a> Solution<'a> {
+    pub(crate) fn new(anagrams: Vec<&'a [Word]>, missing: u128, alphabet: &'a Alphabet) -> Self {
+        Solution {
+            anagrams,
+            missing,
+            alphabet,
+        }
+    }
+
+    /// The anagrams at each position of the solution.
+    pub fn anagrams(&self) -> &[&'a [Word]] {
+        &self.anagrams
+    }
+
+    /// The first anagram at each position of the solution.
+    pub fn words(&self) -> impl Iterator<Item = &'a Word> + '_ {
+        self.anagrams.iter().map(|words| &words[0])
+    }
+
+    /// The letters not covered by any word, in alphabetical order.
+    pub fn missing(&self) -> impl Iterator<Item = char> + '_ {
+        self.alphabet.letters_of(self.missing)
+    }
+
+    /// How common the words are, combined as `score` says.
+    pub fn score(&self, score: Score) -> f64 {
+        score.of(self)
+    }
+
+    /// Sorts the positions alphabetically by their first anagram.
+    pub fn canonicalize(&mut self) {
+        self.anagrams.sort_unstable_by_key(|words| &words[0]);
+    }
+
+    /// Number of combinations of anagrams, i.e. the number of solutions yielded by `expand`.
+    pub fn combinations(&self) -> u64 {
+        self.anagrams
+            .iter()
+            .map(|words| words.len() as u64)
+            .product()
+    }
+
+    /// Every combination of anagrams, each as a solution with a single word per position.
+    pub fn expand(&self) -> impl Iterator<Item = Solution<'a>> + '_ {
+        self.anagrams
+            .iter()
+            .map(|words| words.iter())
+            .multi_cartesian_product()
+            .map(|words| Solution {
+                anagrams: words.into_iter().map(std::slice::from_ref).collect(),
+                ..*self
+            })
+    }
+}
+
+impl PartialEq for Solution<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Solution<'_> {}
+
+impl Ord for Solution<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.anagrams, self.missing).cmp(&(&other.anagrams, other.missing))
+    }
+}
+
+impl PartialOrd for Solution<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Solution<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let anagrams = self.anagrams.iter().map(|words| words.iter().join("/"));
+        write!(f, "{}", anagrams.format(" "))?;
+        if self.missing != 0 {
+            write!(f, " [{}]", self.missing().format(""))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,918 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+
+use crate::{
+    alphabet::Alphabet,
+    bitword::Bitword,
+    checkpoint::{Checkpoint, Log},
+    constraints::{ConstraintCounter, ConstraintReport},
+    error::Error,
+    frequency::{Frequencies, Score},
+    index::{with_index, AnyWordIndex, WordIndex},
+    normalize::{normalize, InputSummary, Rejection},
+    order::{Frequency, LetterOrder},
+    progress::{Progress, ProgressFn, Tracker},
+    report::RunReport,
+    search::Search,
+    shard::{Fnv, Shard},
+    sink::{Counted, FnSink, SolutionSink, VecSink},
+    solution::Solution,
+    stop::{CancellationToken, Stop},
+    verify::{parse_row, Seen, Verification},
+    word::Word,
+};
+
+/// The shape of the puzzle: letter-disjoint words of the allowed lengths covering exactly
+/// `letters` letters, leaving `unused` letters of the alphabet uncovered. The solver may skip at
+/// most `unused` letters.
+#[derive(Clone, Copy)]
+pub(crate) struct Puzzle {
+    /// Bit i is set if words of i + 1 letters are allowed, so words may be as long as an
+    /// alphabet of [`Alphabet::MAX_LEN`] letters.
+    word_lens: u128,
+    /// Exact number of words in a solution, if any number of words covering `letters` letters
+    /// will not do.
+    pub(crate) word_count: Option<usize>,
+    pub(crate) letters: usize,
+    pub(crate) unused: usize,
+}
+
+impl Puzzle {
+    fn new(
+        word_lens: &[usize],
+        word_count: Option<usize>,
+        letters: Option<usize>,
+        alphabet_len: usize,
+    ) -> Result<Puzzle, Error> {
+        let invalid = |reason: String| Err(Error::InvalidPuzzle(reason));
+        if word_lens.is_empty()
+            || word_lens
+                .iter()
+                .any(|len| !(1..=alphabet_len).contains(len))
+        {
+            return invalid(format!("word lengths must be between 1 and {alphabet_len}"));
+        }
+        if word_count == Some(0) {
+            return invalid("a solution needs at least one word".to_owned());
+        }
+        let letters = match (letters, word_lens) {
+            (Some(letters), _) => letters,
+            (None, &[word_len]) => word_len.saturating_mul(word_count.unwrap_or(5)),
+            (None, _) => return invalid("mixed word lengths need a number of letters".to_owned()),
+        };
+        if !(1..=alphabet_len).contains(&letters) {
+            return invalid(format!(
+                "{letters} letters do not fit in a {alphabet_len} letter alphabet"
+            ));
+        }
+        Ok(Puzzle {
+            word_lens: word_lens.iter().fold(0, |lens, len| lens | 1 << (len - 1)),
+            word_count,
+            letters,
+            unused: alphabet_len - letters,
+        })
+    }
+
+    pub(crate) fn allows_len(&self, len: usize) -> bool {
+        (1..=Alphabet::MAX_LEN).contains(&len) && self.word_lens & 1 << (len - 1) != 0
+    }
+
+    /// The length of the longest words allowed.
+    pub(crate) fn max_word_len(&self) -> usize {
+        128 - self.word_lens.leading_zeros() as usize
+    }
+
+    /// The most words a solution can have.
+    pub(crate) fn max_word_count(&self) -> usize {
+        let shortest = self.word_lens.trailing_zeros() as usize + 1;
+        self.word_count.unwrap_or(self.letters / shortest)
+    }
+}
+
+#[derive(Clone)]
+pub struct SolverBuilder {
+    letter_order: Arc<dyn LetterOrder>,
+    word_lens: Vec<usize>,
+    word_count: Option<usize>,
+    letters: Option<usize>,
+    expand_anagrams: bool,
+    sorted: bool,
+    rank: Option<Score>,
+    top: Option<usize>,
+    frequencies: Frequencies,
+    instrument: bool,
+    limit: Option<u64>,
+    timeout: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+    progress: Option<ProgressFn>,
+    shard: Option<Shard>,
+    missing: String,
+    pool: Option<String>,
+    required: Vec<String>,
+    excluded: Vec<String>,
+    alphabet: Alphabet,
+    strip_diacritics: bool,
+    strict: bool,
+}
+
+impl Default for SolverBuilder {
+    fn default() -> Self {
+        SolverBuilder {
+            letter_order: Arc::new(Frequency),
+            word_lens: vec![5],
+            word_count: None,
+            letters: None,
+            expand_anagrams: true,
+            sorted: false,
+            rank: None,
+            top: None,
+            frequencies: Frequencies::default(),
+            instrument: false,
+            limit: None,
+            timeout: None,
+            cancellation: None,
+            progress: None,
+            shard: None,
+            missing: String::new(),
+            pool: None,
+            required: Vec::new(),
+            excluded: Vec::new(),
+            alphabet: Alphabet::english(),
+            strip_diacritics: false,
+            strict: false,
+        }
+    }
+}
+
+impl SolverBuilder {
+    /// Lines parsed together on one thread.
+    const PARSE_CHUNK: usize = 1 << 14;
+
+    /// Number of distinct letters in every word. Defaults to 5.
+    pub fn word_len(self, word_len: usize) -> Self {
+        self.word_lens([word_len])
+    }
+
+    /// Allowed numbers of distinct letters in a word, for solutions mixing words of different
+    /// lengths. Mixed lengths require the number of [`letters`](Self::letters) to be set.
+    pub fn word_lens(mut self, word_lens: impl IntoIterator<Item = usize>) -> Self {
+        self.word_lens = word_lens.into_iter().collect();
+        self
+    }
+
+    /// Number of words in every solution. Defaults to 5 for a single word length, and to any
+    /// number for mixed lengths.
+    pub fn word_count(mut self, word_count: usize) -> Self {
+        self.word_count = Some(word_count);
+        self
+    }
+
+    /// Number of letters covered by every solution. Defaults to the word length times the word
+    /// count for a single word length.
+    pub fn letters(mut self, letters: usize) -> Self {
+        self.letters = Some(letters);
+        self
+    }
+
+    /// Emit every combination of anagrams as its own solution instead of one solution per set
+    /// of anagram classes. Defaults to true.
+    pub fn expand_anagrams(mut self, expand_anagrams: bool) -> Self {
+        self.expand_anagrams = expand_anagrams;
+        self
+    }
+
+    /// Emit the solutions in canonical order, with the words of each solution sorted and the
+    /// solutions sorted lexicographically. The search still runs in parallel, but all solutions
+    /// are held in memory and pushed from a single thread once it is done. Defaults to false.
+    pub fn sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+
+    /// Emit the solutions best first by how common their words are according to the
+    /// [`frequencies`](Self::frequencies), with ties in canonical order. Like
+    /// [`sorted`](Self::sorted), all solutions are held in memory. Defaults to unranked.
+    pub fn rank(mut self, score: Score) -> Self {
+        self.rank = Some(score);
+        self
+    }
+
+    /// Only emit the `top` best ranked solutions. Has no effect unless the solutions are
+    /// [`rank`](Self::rank)ed. Defaults to all of them.
+    pub fn top(mut self, top: usize) -> Self {
+        self.top = Some(top);
+        self
+    }
+
+    /// How common the words are, see [`Word::frequency`]. Defaults to an empty list.
+    pub fn frequencies(mut self, frequencies: Frequencies) -> Self {
+        self.frequencies = frequencies;
+        self
+    }
+
+    /// Count the nodes of the search tree by depth and by letter, see
+    /// [`RunReport::search_stats`]. This slows the search down. Defaults to false.
+    pub fn instrument(mut self, instrument: bool) -> Self {
+        self.instrument = instrument;
+        self
+    }
+
+    /// Stop the search once this many solutions have been pushed, counting every combination of
+    /// anagrams when expanded. With sorted or ranked output, the solutions found first are the
+    /// ones sorted. Defaults to no limit.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Stop the search once it has run this long. Defaults to no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Stop the search once `token` is cancelled. Defaults to none.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Call `callback` from the worker threads whenever a root of the search tree is done, to
+    /// show how far the run has got. Defaults to none.
+    pub fn progress(mut self, callback: impl Fn(&Progress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Only search this shard of the roots of the search tree. The solutions of every shard of a
+    /// run together are the solutions of the whole run, see [`Solver::fingerprint`]. Only applies to
+    /// [`Solver::run`]. Defaults to every root.
+    pub fn shard(mut self, shard: Shard) -> Self {
+        self.shard = Some(shard);
+        self
+    }
+
+    /// Only emit solutions that leave all of these letters unused. Defaults to none.
+    pub fn missing(mut self, letters: &str) -> Self {
+        self.missing = letters.to_owned();
+        self
+    }
+
+    /// Only use these letters, leaving the rest of the alphabet unused. Defaults to the whole
+    /// alphabet.
+    pub fn pool(mut self, letters: &str) -> Self {
+        self.pool = Some(letters.to_owned());
+        self
+    }
+
+    /// Only emit solutions containing all of these words. They must be in the dictionary and
+    /// have no letters in common. Defaults to none.
+    pub fn require<S: AsRef<str>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        self.required = words.into_iter().map(|w| w.as_ref().to_owned()).collect();
+        self
+    }
+
+    /// Leave these words out of the dictionary. Defaults to none.
+    pub fn exclude<S: AsRef<str>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        self.excluded = words.into_iter().map(|w| w.as_ref().to_owned()).collect();
+        self
+    }
+
+    /// The order in which the search covers the letters. Defaults to [`Frequency`].
+    pub fn letter_order(mut self, letter_order: impl LetterOrder + 'static) -> Self {
+        self.letter_order = Arc::new(letter_order);
+        self
+    }
+
+    /// The letters words are made of. Defaults to a-z.
+    pub fn alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Replace accented letters in the dictionary that are not in the alphabet by their base
+    /// letter, e.g. é by e. Defaults to false.
+    pub fn strip_diacritics(mut self, strip_diacritics: bool) -> Self {
+        self.strip_diacritics = strip_diacritics;
+        self
+    }
+
+    /// Fail on dictionary lines with characters outside of the alphabet instead of skipping
+    /// them. Defaults to false.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Builds the word index from the dictionary, one word per line. Lines are trimmed and
+    /// case-folded, and the ones that are not words of the right length with distinct letters
+    /// are skipped and counted in [`Solver::input_summary`].
+    pub fn build<I>(self, lines: I) -> Result<Solver, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]> + Sync,
+    {
+        let alphabet = self.alphabet;
+        let puzzle = Puzzle::new(
+            &self.word_lens,
+            self.word_count,
+            self.letters,
+            alphabet.len(),
+        )?;
+        // letters are case-folded like the words of the dictionary
+        let bitword =
+            |letters| alphabet.bitword(&normalize(letters, &alphabet, self.strip_diacritics));
+        let mut unavailable = bitword(&self.missing)?;
+        if let Some(pool) = &self.pool {
+            unavailable |= !bitword(pool)? & (u128::MAX >> (128 - alphabet.len()));
+        }
+        let available = alphabet.len() - unavailable.count_ones() as usize;
+        if available < puzzle.letters {
+            return Err(Error::InvalidPuzzle(format!(
+                "{} letters do not fit in the {available} available letters",
+                puzzle.letters
+            )));
+        }
+        let normalize_word =
+            |word: &String| normalize(word, &alphabet, self.strip_diacritics).into_owned();
+        let required: Vec<String> = self.required.iter().map(normalize_word).collect();
+        let excluded: HashSet<String> = self.excluded.iter().map(normalize_word).collect();
+        let frequencies = self
+            .frequencies
+            .normalized(&alphabet, self.strip_diacritics);
+        let start = Instant::now();
+        let lines: Vec<I::Item> = lines.into_iter().collect();
+        // parsed in chunks on all threads and put back together in line order
+        let chunks: Vec<_> = lines
+            .par_chunks(Self::PARSE_CHUNK)
+            .map(|chunk| {
+                let mut summary = InputSummary::default();
+                let mut words = Vec::new();
+                let mut removed = Vec::new();
+                for line in chunk {
+                    let line = String::from_utf8_lossy(line.as_ref());
+                    let word = normalize(&line, &alphabet, self.strip_diacritics);
+                    let word = Word::new(&word, &alphabet, &puzzle).map(|mut word| {
+                        word.frequency = frequencies.get(word.as_str()).copied().unwrap_or(0);
+                        word
+                    });
+                    summary.record(line.trim(), word.as_ref().map(|_| ()).map_err(|e| *e));
+                    match word {
+                        Ok(word) if excluded.contains(word.as_str()) => removed.push(word),
+                        Ok(word) => words.push(word),
+                        Err(_) => (),
+                    }
+                }
+                (summary, words, removed)
+            })
+            .collect();
+        let mut summary = InputSummary::default();
+        let mut words = Vec::with_capacity(chunks.iter().map(|(_, words, _)| words.len()).sum());
+        let mut removed = Vec::new();
+        for (chunk_summary, chunk_words, chunk_removed) in chunks {
+            summary.append(chunk_summary);
+            words.extend(chunk_words);
+            removed.extend(chunk_removed);
+        }
+        if let Some((line, word)) = summary.invalid.first().filter(|_| self.strict) {
+            return Err(Error::InvalidWord {
+                line: *line,
+                word: word.clone(),
+            });
+        }
+
+        let invalid = |reason: String| Err(Error::InvalidConstraint(reason));
+        let mut required_words: Vec<Word> = Vec::new();
+        for text in required {
+            if excluded.contains(&text) {
+                return invalid(format!("{text:?} is both required and excluded"));
+            }
+            let Some(word) = words.iter().find(|w| w.as_str() == text) else {
+                return invalid(format!("required word {text:?} is not in the dictionary"));
+            };
+            if word.bitword & unavailable != 0 {
+                return invalid(format!("required word {word} uses unavailable letters"));
+            }
+            if let Some(other) = required_words
+                .iter()
+                .find(|w| w.bitword & word.bitword != 0)
+            {
+                return invalid(format!("required words {other} and {word} share letters"));
+            }
+            required_words.push(word.clone());
+        }
+        removed.sort_unstable();
+        removed.dedup();
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        let word_index = AnyWordIndex::new(words, &alphabet, &*self.letter_order)?;
+        let buckets = word_index.bucket_sizes();
+        let report = RunReport {
+            parse,
+            index: start.elapsed(),
+            letter_order: self.letter_order.name(),
+            lines: summary.lines,
+            words: summary.words,
+            classes: buckets.iter().map(|(_, classes)| classes).sum(),
+            buckets: buckets
+                .into_iter()
+                .map(|(letter, classes)| (alphabet.letter(letter), classes))
+                .collect(),
+            ..RunReport::default()
+        };
+
+        Ok(Solver {
+            summary,
+            report,
+            word_index,
+            alphabet,
+            unavailable,
+            required: required_words,
+            excluded: removed,
+            puzzle,
+            expand_anagrams: self.expand_anagrams,
+            sorted: self.sorted,
+            rank: self.rank,
+            top: self.top,
+            strip_diacritics: self.strip_diacritics,
+            instrument: self.instrument,
+            limit: self.limit,
+            timeout: self.timeout,
+            cancellation: self.cancellation,
+            progress: self.progress,
+            shard: self.shard,
+            letter_order: self.letter_order,
+        })
+    }
+}
+
+pub struct Solver {
+    summary: InputSummary,
+    /// The phases of building the solver.
+    report: RunReport,
+    word_index: AnyWordIndex,
+    alphabet: Alphabet,
+    puzzle: Puzzle,
+    expand_anagrams: bool,
+    sorted: bool,
+    rank: Option<Score>,
+    top: Option<usize>,
+    strip_diacritics: bool,
+    instrument: bool,
+    limit: Option<u64>,
+    timeout: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+    progress: Option<ProgressFn>,
+    shard: Option<Shard>,
+    letter_order: Arc<dyn LetterOrder>,
+    /// Letters no word may use: the missing letters and the ones outside the pool.
+    unavailable: u128,
+    required: Vec<Word>,
+    /// The excluded words that were left out of the dictionary.
+    excluded: Vec<Word>,
+}
+
+impl Solver {
+    pub fn builder() -> SolverBuilder {
+        SolverBuilder::default()
+    }
+
+    /// How the lines of the dictionary were handled.
+    pub fn input_summary(&self) -> &InputSummary {
+        &self.summary
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    /// The most words a solution can have, which is the number of words in every solution
+    /// unless the word lengths are mixed.
+    pub fn max_word_count(&self) -> usize {
+        self.puzzle.max_word_count()
+    }
+
+    /// Searches in parallel, pushing every solution into `sink` until the limit, the timeout or
+    /// the cancellation token stops the search. Returns how long building the solver and each
+    /// phase of the search took.
+    pub fn run<'a, S>(&'a self, sink: &S) -> RunReport
+    where
+        S: SolutionSink<'a>,
+    {
+        let mut report = self.report.clone();
+        let sink = Counted::new(sink);
+        with_index!(&self.word_index, index => {
+            self.search(index, &self.required).run(&sink, &mut report)
+        });
+        report.solutions = sink.count();
+        report
+    }
+
+    /// Pushes the `limit` combinations of words that cover the most letters, best first, which
+    /// may be fewer letters and fewer words than a solution needs. The words are disjoint except
+    /// for at most one, which may share letters with the others if it adds more letters than it
+    /// shares, and each letter shared counts against the letters covered. Four disjoint words
+    /// covering 20 letters and a fifth adding 3 letters and sharing 2 score 21. Combinations of
+    /// anagram classes are ranked, so with expanded anagrams more than `limit` solutions may be
+    /// pushed.
+    pub fn run_best<'a, S>(&'a self, limit: usize, sink: &S) -> RunReport
+    where
+        S: SolutionSink<'a>,
+    {
+        let mut report = self.report.clone();
+        let sink = Counted::new(sink);
+        with_index!(&self.word_index, index => {
+            self.search(index, &self.required).run_best(limit, &sink, &mut report)
+        });
+        report.solutions = sink.count();
+        report
+    }
+
+    /// Like [`run`](Self::run), but appends every root of the search tree to `log` once it is
+    /// done, with its solutions, so that a run cut short by a crash can resume from the log. To
+    /// resume, pass the [`Checkpoint`] read from the log as `resume` and a `log` that appends to
+    /// it: the roots done are skipped and their solutions are pushed first. Fails if `resume`
+    /// is from a run with a different dictionary or different parameters, or if the log can not
+    /// be written. Sorted and ranked runs can not be checkpointed.
+    pub fn run_checkpointed<'a, S, W>(
+        &'a self,
+        sink: &S,
+        resume: Option<&Checkpoint>,
+        mut log: W,
+    ) -> Result<RunReport, Error>
+    where
+        S: SolutionSink<'a>,
+        W: Write + Send + 'a,
+    {
+        if self.sorted || self.rank.is_some() {
+            let reason = "sorted and ranked runs can not be checkpointed";
+            return Err(Error::Checkpoint(reason.to_owned()));
+        }
+        let write_error = |e| Error::Checkpoint(format!("could not write the log: {e}"));
+        let fingerprint = self.fingerprint();
+        let sink = Counted::new(sink);
+        let (done, resumed) = match resume {
+            Some(checkpoint) => {
+                if checkpoint.fingerprint != fingerprint || checkpoint.shard != self.shard {
+                    return Err(Error::Checkpoint(
+                        "the log is from a run with a different dictionary or different parameters"
+                            .to_owned(),
+                    ));
+                }
+                let resumed = self.resumed_solutions(checkpoint)?;
+                // end a line cut off by a crash
+                log.write_all(b"\n").map_err(write_error)?;
+                (checkpoint.roots.keys().copied().collect(), resumed)
+            }
+            None => {
+                let header = Checkpoint::header(fingerprint, self.shard);
+                log.write_all(header.as_bytes()).map_err(write_error)?;
+                (HashSet::new(), Vec::new())
+            }
+        };
+        log.flush().map_err(write_error)?;
+
+        let mut report = self.report.clone();
+        report.resumed = done.len();
+        let log = Log::new(log, done);
+        let error = with_index!(&self.word_index, index => {
+            let search = Search {
+                checkpoint: Some(log),
+                ..self.search(index, &self.required)
+            };
+            // the solutions of the roots done count against the limit and in the progress
+            let mut buffer = sink.buffer();
+            for solution in &resumed {
+                search.push(&sink, &mut buffer, solution);
+            }
+            sink.flush(buffer);
+            search.run(&sink, &mut report);
+            search.checkpoint.and_then(Log::into_error)
+        });
+        if let Some(e) = error {
+            return Err(write_error(e));
+        }
+        report.solutions = sink.count();
+        Ok(report)
+    }
+
+    /// The class-level solutions of the roots done in `checkpoint`.
+    fn resumed_solutions(&self, checkpoint: &Checkpoint) -> Result<Vec<Solution<'_>>, Error> {
+        let dictionary = self.dictionary();
+        let mut solutions = Vec::new();
+        for row in checkpoint.solutions() {
+            let row = self.read_row(row.as_bytes());
+            let invalid = |reason| Error::Checkpoint(format!("{reason} in a solution of the log"));
+            let positions = self.check_row(&dictionary, &row).map_err(invalid)?;
+            let anagrams = positions
+                .iter()
+                .map(|words| {
+                    // required words are placed alone, as in the search
+                    if let [word] = words[..] {
+                        if let Some(required) = self.required.iter().find(|r| *r == word) {
+                            return Some(std::slice::from_ref(required));
+                        }
+                    }
+                    let anagrams = with_index!(&self.word_index, index => index.anagrams(words[0]));
+                    anagrams.filter(|anagrams| anagrams.len() == words.len())
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid("a position without all of its anagrams".to_owned()))?;
+            solutions.push(Solution::new(
+                anagrams,
+                self.missing(&positions),
+                &self.alphabet,
+            ));
+        }
+        Ok(solutions)
+    }
+
+    fn search<'a, B: Bitword>(
+        &'a self,
+        index: &'a WordIndex<B>,
+        required: &[Word],
+    ) -> Search<'a, B> {
+        let required_words = required.iter().map(|word| {
+            let word = index.find(word).expect("required words are in the index");
+            std::slice::from_ref(word)
+        });
+        let required_bitword = required.iter().fold(0, |bitword, w| bitword | w.bitword);
+        Search {
+            index,
+            alphabet: &self.alphabet,
+            puzzle: self.puzzle,
+            expand_anagrams: self.expand_anagrams,
+            sorted: self.sorted,
+            rank: self.rank,
+            top: self.top,
+            instrument: self.instrument,
+            unavailable: index.transform(self.unavailable),
+            required: required_words.collect(),
+            required_bitword: index.transform(required_bitword),
+            stop: Stop::new(self.limit, self.timeout, self.cancellation.clone()),
+            progress: self.progress.clone().map(Tracker::new),
+            shard: self.shard,
+            checkpoint: None,
+        }
+    }
+
+    /// Counts how many solutions each required and excluded word eliminates, by searching again
+    /// without them. The search has the limit, timeout, cancellation and shard of a run, so the
+    /// counts may be partial, and the counts of every shard add up to those of the whole run.
+    pub fn constraint_report(&self) -> ConstraintReport {
+        let mut words = self.word_index.words();
+        words.extend(self.excluded.iter().cloned());
+        let word_index = AnyWordIndex::new(words, &self.alphabet, &*self.letter_order)
+            .expect("the letter order was valid for the dictionary");
+        let counter = ConstraintCounter::new(&self.required, &self.excluded);
+        let mut report = RunReport::default();
+        with_index!(&word_index, index => {
+            let search = Search {
+                expand_anagrams: false,
+                sorted: false,
+                rank: None,
+                instrument: false,
+                progress: None,
+                ..self.search(index, &[])
+            };
+            search.run(&counter, &mut report);
+        });
+        counter.into_report(self.shard, report.stopped)
+    }
+
+    /// Searches in parallel, calling `f` from the worker threads for every solution found.
+    pub fn for_each<'a, F>(&'a self, f: F)
+    where
+        F: Fn(&Solution<'a>) + Sync,
+    {
+        self.run(&FnSink(f));
+    }
+
+    /// Collects every solution before returning them.
+    pub fn solutions(&self) -> impl Iterator<Item = Solution<'_>> {
+        let sink = VecSink::new();
+        self.run(&sink);
+        sink.into_inner().into_iter()
+    }
+
+    /// Checks solutions in the text format, one per line, against the dictionary and a fresh
+    /// solve. Every word of a row must be in the dictionary, the words must be letter-disjoint,
+    /// leave the unavailable letters unused, include the required words and make up a solution,
+    /// and together the rows must hold every solution exactly once.
+    pub fn verify<I>(&self, lines: I) -> Verification
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let dictionary = self.dictionary();
+        let mut verification = Verification::default();
+        let mut seen = Seen::default();
+        for (i, line) in lines.into_iter().enumerate() {
+            let row = self.read_row(line.as_ref());
+            if row.is_empty() {
+                continue;
+            }
+            verification.rows += 1;
+            match self.check_row(&dictionary, &row) {
+                Ok(_) => {
+                    verification.valid += 1;
+                    if let Some(first) = seen.insert(i + 1, &row) {
+                        verification.duplicates.push((i + 1, first));
+                    }
+                }
+                Err(reason) => verification.invalid.push((i + 1, reason)),
+            }
+        }
+
+        // every solution, whatever the limit and timeout
+        let solutions = VecSink::new();
+        with_index!(&self.word_index, index => {
+            let search = Search {
+                stop: Stop::default(),
+                progress: None,
+                shard: None,
+                ..self.search(index, &self.required)
+            };
+            search.run(&solutions, &mut RunReport::default());
+        });
+        for solution in solutions.into_inner() {
+            for mut solution in solution.expand() {
+                let words = solution.words().map(|w| w.as_str().to_owned()).collect();
+                if !seen.contains(words) {
+                    solution.canonicalize();
+                    verification.missing.push(solution.to_string());
+                }
+            }
+        }
+        verification.missing.sort_unstable();
+        verification
+    }
+
+    /// Reads solutions in the text format, one per line, e.g. the shards of a run to merge.
+    /// Collapsed anagrams are expanded, so every solution has a single word per position, in
+    /// canonical order. Fails on the first row that is not a solution.
+    pub fn parse_solutions<I>(&self, lines: I) -> Result<Vec<Solution<'_>>, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let dictionary = self.dictionary();
+        let mut solutions = Vec::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            let row = self.read_row(line.as_ref());
+            if row.is_empty() {
+                continue;
+            }
+            let positions =
+                self.check_row(&dictionary, &row)
+                    .map_err(|reason| Error::InvalidSolution {
+                        line: i + 1,
+                        reason,
+                    })?;
+            let missing = self.missing(&positions);
+            let combinations = positions
+                .iter()
+                .map(|words| words.iter().copied())
+                .multi_cartesian_product();
+            for words in combinations {
+                let anagrams = words.into_iter().map(std::slice::from_ref).collect();
+                let mut solution = Solution::new(anagrams, missing, &self.alphabet);
+                solution.canonicalize();
+                solutions.push(solution);
+            }
+        }
+        Ok(solutions)
+    }
+
+    /// A hash of everything that decides the roots of the search tree and the solutions below
+    /// them: the alphabet, the letter order, the dictionary, the puzzle and the constraints. The
+    /// shards of a run only fit together if their fingerprints match.
+    pub fn fingerprint(&self) -> u64 {
+        let mut fnv = Fnv::default();
+        fnv.write_str(&self.alphabet.letters().iter().collect::<String>());
+        let buckets = self.word_index.bucket_sizes();
+        fnv.write_usize(buckets.len());
+        for (letter, classes) in buckets {
+            fnv.write_usize(letter);
+            fnv.write_usize(classes);
+        }
+        let words = self.word_index.words();
+        fnv.write_usize(words.len());
+        for word in &words {
+            fnv.write_str(word.as_str());
+        }
+        fnv.write_u128(self.puzzle.word_lens);
+        // no word count is 0, which is not a valid count
+        fnv.write_usize(self.puzzle.word_count.unwrap_or(0));
+        fnv.write_usize(self.puzzle.letters);
+        fnv.write_usize(self.puzzle.unused);
+        fnv.write_u128(self.unavailable);
+        fnv.write_usize(self.required.len());
+        for word in &self.required {
+            fnv.write_str(word.as_str());
+        }
+        fnv.finish()
+    }
+
+    /// The letters of the alphabet not in any of the words at `positions`.
+    fn missing(&self, positions: &[Vec<&Word>]) -> u128 {
+        let covered = positions
+            .iter()
+            .fold(0, |covered, words| covered | words[0].bitword);
+        !covered & u128::mask(0..self.alphabet.len())
+    }
+
+    /// Every word of the dictionary by its text.
+    fn dictionary(&self) -> HashMap<&str, &Word> {
+        with_index!(&self.word_index, index => {
+            index.words().map(|word| (word.as_str(), word)).collect()
+        })
+    }
+
+    /// The normalised anagrams at each position of a line in the text format.
+    fn read_row(&self, line: &[u8]) -> Vec<Vec<String>> {
+        let line = String::from_utf8_lossy(line);
+        parse_row(&line)
+            .iter()
+            .map(|position| {
+                position
+                    .iter()
+                    .map(|word| normalize(word, &self.alphabet, self.strip_diacritics).into_owned())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The words at each position of `row`, or why it is not a solution.
+    fn check_row<'a>(
+        &self,
+        dictionary: &HashMap<&str, &'a Word>,
+        row: &[Vec<String>],
+    ) -> Result<Vec<Vec<&'a Word>>, String> {
+        let mut positions: Vec<Vec<&Word>> = Vec::with_capacity(row.len());
+        for position in row {
+            let anagrams = position
+                .iter()
+                .map(|text| {
+                    dictionary
+                        .get(text.as_str())
+                        .copied()
+                        .ok_or_else(|| self.why_not_in_dictionary(text))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let word = anagrams[0];
+            if let Some(anagram) = anagrams.iter().find(|a| a.bitword != word.bitword) {
+                return Err(format!("{word} and {anagram} are not anagrams"));
+            }
+            if word.bitword & self.unavailable != 0 {
+                return Err(format!("{word} uses unavailable letters"));
+            }
+            let other = positions
+                .iter()
+                .find(|other| other[0].bitword & word.bitword != 0);
+            if let Some(other) = other {
+                return Err(format!("{} and {word} share letters", other[0]));
+            }
+            positions.push(anagrams);
+        }
+        let words = positions.iter().map(|anagrams| anagrams[0]).collect_vec();
+        let letters: usize = words.iter().map(|word| word.len()).sum();
+        if letters != self.puzzle.letters {
+            return Err(format!(
+                "covers {letters} letters instead of {}",
+                self.puzzle.letters
+            ));
+        }
+        if let Some(word_count) = self.puzzle.word_count.filter(|n| *n != words.len()) {
+            return Err(format!("has {} words instead of {word_count}", words.len()));
+        }
+        let required = self.required.iter().find(|required| {
+            let mut anagrams = positions.iter().flatten();
+            !anagrams.any(|word| word == required)
+        });
+        if let Some(required) = required {
+            return Err(format!("does not have the required word {required}"));
+        }
+        Ok(positions)
+    }
+
+    fn why_not_in_dictionary(&self, text: &str) -> String {
+        match Word::new(text, &self.alphabet, &self.puzzle) {
+            Ok(_) => format!("{text:?} is not in the dictionary"),
+            Err(Rejection::Empty) => "a word is empty".to_owned(),
+            Err(Rejection::InvalidCharacter) => {
+                format!("{text:?} has characters outside of the alphabet")
+            }
+            Err(Rejection::WrongLength) => format!("{text:?} has the wrong number of letters"),
+            Err(Rejection::RepeatedLetter) => format!("{text:?} has repeated letters"),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::{fmt::Display, ops::AddAssign};
+
+use itertools::Itertools;
+
+use crate::format::json_string;
+
+/// What the search did at a set of nodes of the search tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeCounts {
+    /// Nodes expanded, not counting the ones that end in a solution or at the word count.
+    pub nodes: u64,
+    /// Anagram classes tested against the filter.
+    pub tested: u64,
+    /// Classes that fit and were searched below.
+    pub extended: u64,
+    /// Branches that skip the letter instead of covering it.
+    pub skipped: u64,
+}
+
+impl AddAssign for NodeCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.tested += other.tested;
+        self.extended += other.extended;
+        self.skipped += other.skipped;
+    }
+}
+
+/// The shape of the search tree, to see where a dictionary spends its time.
+#[derive(Clone, Debug, Default)]
+pub struct SearchStats {
+    /// Counts by the number of words placed by the search, starting with the choice of the
+    /// first word.
+    pub by_depth: Vec<NodeCounts>,
+    /// Counts by the letter covered at the node, i.e. the bucket its classes come from, in the
+    /// order the search covers the letters.
+    pub by_letter: Vec<(char, NodeCounts)>,
+}
+
+impl SearchStats {
+    pub fn total(&self) -> NodeCounts {
+        let mut total = NodeCounts::default();
+        for counts in &self.by_depth {
+            total += *counts;
+        }
+        total
+    }
+
+    /// The stats as a JSON object.
+    pub(crate) fn to_json(&self) -> String {
+        let counts = |counts: &NodeCounts| {
+            format!(
+                r#"{{"nodes":{},"tested":{},"extended":{},"skipped":{}}}"#,
+                counts.nodes, counts.tested, counts.extended, counts.skipped
+            )
+        };
+        let by_letter = self
+            .by_letter
+            .iter()
+            .map(|(letter, c)| format!("[{},{}]", json_string(&letter.to_string()), counts(c)));
+        format!(
+            r#"{{"by_depth":[{}],"by_letter":[{}]}}"#,
+            self.by_depth.iter().map(counts).format(","),
+            by_letter.format(",")
+        )
+    }
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let row = |f: &mut std::fmt::Formatter<'_>, label: &str, counts: &NodeCounts| {
+            writeln!(
+                f,
+                "{label:<8}{:>14}{:>14}{:>14}{:>14}",
+                counts.nodes, counts.tested, counts.extended, counts.skipped
+            )
+        };
+        writeln!(
+            f,
+            "{:<8}{:>14}{:>14}{:>14}{:>14}",
+            "depth", "nodes", "tested", "extended", "skipped"
+        )?;
+        for (depth, counts) in self.by_depth.iter().enumerate() {
+            row(f, &depth.to_string(), counts)?;
+        }
+        row(f, "total", &self.total())?;
+        writeln!(
+            f,
+            "{:<8}{:>14}{:>14}{:>14}{:>14}",
+            "letter", "nodes", "tested", "extended", "skipped"
+        )?;
+        let by_letter = self.by_letter.iter().filter(|(_, counts)| counts.nodes > 0);
+        for (letter, counts) in by_letter {
+            row(f, &letter.to_string(), counts)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where the search records what it does at every node. The search is generic over it, so
+/// counting costs nothing unless asked for.
+pub(crate) trait Counters: Send {
+    fn new(depths: usize, letters: usize) -> Self;
+
+    /// The counts of the nodes at `depth` covering `letter`, if counting.
+    fn at(&mut self, depth: usize, letter: usize) -> Option<&mut NodeCounts>;
+
+    fn merge(self, other: Self) -> Self;
+}
+
+impl Counters for () {
+    fn new(_depths: usize, _letters: usize) -> Self {}
+
+    #[inline(always)]
+    fn at(&mut self, _depth: usize, _letter: usize) -> Option<&mut NodeCounts> {
+        None
+    }
+
+    fn merge(self, _other: Self) -> Self {}
+}
+
+/// Counts by depth and by transformed letter.
+pub(crate) struct TreeCounts {
+    letters: usize,
+    counts: Vec<NodeCounts>,
+}
+
+impl TreeCounts {
+    /// The counts by depth and by letter, naming each transformed letter with `letter`.
+    pub(crate) fn into_stats(self, letter: impl Fn(usize) -> char) -> SearchStats {
+        let mut by_depth = vec![NodeCounts::default(); self.counts.len() / self.letters];
+        let mut by_letter = vec![NodeCounts::default(); self.letters];
+        for (i, counts) in self.counts.into_iter().enumerate() {
+            by_depth[i / self.letters] += counts;
+            by_letter[i % self.letters] += counts;
+        }
+        while by_depth.last().is_some_and(|counts| counts.nodes == 0) {
+            by_depth.pop();
+        }
+        let by_letter = by_letter
+            .into_iter()
+            .enumerate()
+            .rev()
+            .map(|(i, counts)| (letter(i), counts))
+            .collect();
+        SearchStats {
+            by_depth,
+            by_letter,
+        }
+    }
+}
+
+impl Counters for TreeCounts {
+    fn new(depths: usize, letters: usize) -> Self {
+        TreeCounts {
+            letters,
+            counts: vec![NodeCounts::default(); depths * letters],
+        }
+    }
+
+    fn at(&mut self, depth: usize, letter: usize) -> Option<&mut NodeCounts> {
+        self.counts.get_mut(depth * self.letters + letter)
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        for (counts, other) in self.counts.iter_mut().zip(other.counts) {
+            *counts += other;
+        }
+        self
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// Stops a running search from another thread. Clones share the same state, so keep one and
+/// hand a clone to [`SolverBuilder::cancellation`](crate::SolverBuilder::cancellation).
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the search return soon, after flushing the solutions found so far.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Why a search ended before covering the whole search tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Limit,
+    Timeout,
+    Cancelled,
+}
+
+impl StopReason {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            StopReason::Limit => "limit",
+            StopReason::Timeout => "timeout",
+            StopReason::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            StopReason::Limit => "the solution limit was reached",
+            StopReason::Timeout => "the timeout ran out",
+            StopReason::Cancelled => "the search was cancelled",
+        })
+    }
+}
+
+/// When a run should end early, shared by all worker threads.
+#[derive(Debug, Default)]
+pub(crate) struct Stop {
+    limit: Option<u64>,
+    deadline: Option<Instant>,
+    token: Option<CancellationToken>,
+    /// Solutions handed out so far, only counted with a limit.
+    found: AtomicU64,
+    stopped: AtomicBool,
+    reason: OnceLock<StopReason>,
+}
+
+impl Stop {
+    pub(crate) fn new(
+        limit: Option<u64>,
+        timeout: Option<Duration>,
+        token: Option<CancellationToken>,
+    ) -> Self {
+        Stop {
+            limit,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            token,
+            ..Stop::default()
+        }
+    }
+
+    fn stop(&self, reason: StopReason) {
+        let _ = self.reason.set(reason);
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the run has been stopped, without looking at the clock or the token.
+    #[inline]
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Whether the run has been stopped, cancelled or has run out of time.
+    pub(crate) fn check(&self) -> bool {
+        if self
+            .token
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+        {
+            self.stop(StopReason::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.stop(StopReason::Timeout);
+        }
+        self.is_stopped()
+    }
+
+    /// How many of `n` more solutions fit within the limit.
+    pub(crate) fn take(&self, n: u64) -> u64 {
+        let Some(limit) = self.limit else {
+            return n;
+        };
+        let found = self.found.fetch_add(n, Ordering::Relaxed);
+        if found + n >= limit {
+            self.stop(StopReason::Limit);
+        }
+        limit.saturating_sub(found).min(n)
+    }
+
+    pub(crate) fn reason(&self) -> Option<StopReason> {
+        self.reason.get().copied()
+    }
+}
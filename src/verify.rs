@@ -0,0 +1,76 @@
+use std::{collections::HashMap, fmt::Display};
+
+use itertools::Itertools;
+
+/// The outcome of checking a file of solutions against the dictionary and a fresh solve.
+#[derive(Clone, Debug, Default)]
+pub struct Verification {
+    /// Number of rows checked, not counting empty lines.
+    pub rows: usize,
+    /// Number of rows that are solutions.
+    pub valid: usize,
+    /// Line number, starting at 1, and reason of every row that is not a solution.
+    pub invalid: Vec<(usize, String)>,
+    /// Line number of every repeated solution along with the line it first appeared on.
+    pub duplicates: Vec<(usize, usize)>,
+    /// The solutions found by a fresh solve that are not in the file, in canonical order.
+    pub missing: Vec<String>,
+}
+
+impl Verification {
+    /// Whether every row is a distinct solution and no solution is missing.
+    pub fn is_ok(&self) -> bool {
+        self.invalid.is_empty() && self.duplicates.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl Display for Verification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} rows, {} valid, {} invalid, {} duplicates, {} missing",
+            self.rows,
+            self.valid,
+            self.invalid.len(),
+            self.duplicates.len(),
+            self.missing.len()
+        )
+    }
+}
+
+/// The positions of a row in the text format, each with its anagrams, e.g.
+/// `ambry/barmy fldxt pucks vejoz whing [q]`. The unused letters in brackets are optional.
+pub(crate) fn parse_row(row: &str) -> Vec<Vec<&str>> {
+    let words = row.split('[').next().unwrap_or_default();
+    words
+        .split_whitespace()
+        .map(|position| position.split('/').collect())
+        .collect()
+}
+
+/// Remembers the rows seen so far by the combinations of words they stand for.
+#[derive(Default)]
+pub(crate) struct Seen {
+    lines: HashMap<Vec<String>, usize>,
+}
+
+impl Seen {
+    /// Records every combination of anagrams of `row`, returning the line of a previous row
+    /// with one of the same combinations.
+    pub(crate) fn insert(&mut self, line: usize, row: &[Vec<String>]) -> Option<usize> {
+        let mut previous = None;
+        for words in row.iter().multi_cartesian_product() {
+            let key = words.into_iter().cloned().sorted_unstable().collect();
+            let first = *self.lines.entry(key).or_insert(line);
+            if first != line {
+                previous = previous.or(Some(first));
+            }
+        }
+        previous
+    }
+
+    pub(crate) fn contains(&self, words: Vec<String>) -> bool {
+        self.lines
+            .contains_key(&words.into_iter().sorted_unstable().collect_vec())
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+};
+
+use crate::{alphabet::Alphabet, normalize::Rejection, solver::Puzzle};
+
+/// A dictionary word made of distinct letters, along with its letters as a bitword.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Word {
+    /// Bit i is set for the i-th letter of the alphabet.
+    pub(crate) bitword: u128,
+    /// Index of every letter in the alphabet.
+    letters: Box<[u8]>,
+    text: Box<str>,
+    /// Occurrences in the frequency list, if any.
+    pub(crate) frequency: u64,
+}
+
+impl Word {
+    /// Expects a normalised word, see [`normalize`](crate::normalize::normalize).
+    pub(crate) fn new(word: &str, alphabet: &Alphabet, puzzle: &Puzzle) -> Result<Word, Rejection> {
+        if word.is_empty() {
+            return Err(Rejection::Empty);
+        }
+        // find out whether the word is kept before allocating, as most lines are not
+        let mut bitword = 0u128;
+        let mut len = 0;
+        let mut repeated = false;
+        for c in word.chars() {
+            let letter = alphabet.index_of(c).ok_or(Rejection::InvalidCharacter)?;
+            repeated |= bitword & (1 << letter) != 0;
+            bitword |= 1 << letter;
+            len += 1;
+        }
+        if !puzzle.allows_len(len) {
+            return Err(Rejection::WrongLength);
+        }
+        if repeated {
+            return Err(Rejection::RepeatedLetter);
+        }
+        Ok(Word {
+            bitword,
+            letters: word
+                .chars()
+                .filter_map(|c| alphabet.index_of(c))
+                .map(|index| index as u8)
+                .collect(),
+            text: word.into(),
+            frequency: 0,
+        })
+    }
+
+    /// Number of letters.
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// How often the word occurs according to the frequency list, 0 if it is not listed.
+    pub fn frequency(&self) -> u64 {
+        self.frequency
+    }
+}
+
+/// Words are ordered alphabetically, following the order of their alphabet.
+impl Ord for Word {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.letters.cmp(&other.letters)
+    }
+}
+
+impl PartialOrd for Word {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Debug for Word {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#b} {self}", self.bitword)
+    }
+}
+
+impl Display for Word {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
@@ -0,0 +1,68 @@
+//! Checks how dictionary lines are normalised into words: surrounding whitespace and CRLF line
+//! endings are trimmed, letters are case-folded and accents are stripped on request, and lines
+//! with characters outside of the alphabet are reported, or fail the build in strict mode.
+
+use five_five::{Alphabet, Error, Solver, SolverBuilder};
+
+/// "ef" is only in the dictionary once its accent is stripped, and "g'h" never is.
+const DICTIONARY: [&str; 7] = ["  AB\r", "cd", "", "Éf", "g'h", "gh\t", "abc"];
+
+fn builder() -> SolverBuilder {
+    Solver::builder()
+        .alphabet(Alphabet::new("abcdefgh").unwrap())
+        .word_len(2)
+        .word_count(4)
+}
+
+fn solutions(solver: &Solver) -> Vec<String> {
+    solver.solutions().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn normalised() {
+    let solver = builder().build(DICTIONARY).unwrap();
+    let summary = solver.input_summary();
+    assert_eq!((summary.lines, summary.words), (7, 3));
+    assert_eq!((summary.empty, summary.wrong_length), (1, 1));
+    let invalid = [(4, "Éf".to_owned()), (5, "g'h".to_owned())];
+    assert_eq!(summary.invalid, invalid);
+    assert!(solutions(&solver).is_empty());
+
+    let solver = builder().strip_diacritics(true).build(DICTIONARY).unwrap();
+    assert_eq!(solver.input_summary().words, 4);
+    assert_eq!(solver.input_summary().invalid, [(5, "g'h".to_owned())]);
+    assert_eq!(solutions(&solver), ["ab cd ef gh"]);
+    // letters written with a combining mark are stripped the same way
+    let dictionary = ["ab", "cd", "E\u{301}F", "gh"];
+    let solver = builder().strip_diacritics(true).build(dictionary).unwrap();
+    assert_eq!(solutions(&solver), ["ab cd ef gh"]);
+}
+
+#[test]
+fn invalid_lines_across_chunks() {
+    // enough lines to be parsed in several chunks
+    let mut dictionary = vec!["ab"; 100_000];
+    dictionary.extend(["cd", "e-f", "ef", "gh"]);
+    let solver = builder().build(&dictionary).unwrap();
+    assert_eq!(
+        solver.input_summary().invalid,
+        [(100_002, "e-f".to_owned())]
+    );
+    assert_eq!(solutions(&solver), ["ab cd ef gh"]);
+}
+
+#[test]
+fn strict() {
+    let line = |result: Result<Solver, Error>| match result {
+        Err(Error::InvalidWord { line, word }) => (line, word),
+        Err(e) => panic!("unexpected error {e}"),
+        Ok(_) => panic!("no error"),
+    };
+    let strict = builder().strict(true);
+    assert_eq!(line(strict.clone().build(DICTIONARY)), (4, "Éf".to_owned()));
+    let stripped = strict.clone().strip_diacritics(true).build(DICTIONARY);
+    assert_eq!(line(stripped), (5, "g'h".to_owned()));
+    assert!(strict
+        .build(["  AB\r", "cd", "", "ef", "gh\t", "abc"])
+        .is_ok());
+}
@@ -0,0 +1,437 @@
+//! Compares the solver with a slow reference solver that tries every combination of words, on
+//! hand-crafted and random small dictionaries.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashSet},
+};
+
+use five_five::{Alphabet, Solver, VecSink};
+
+type Solutions = BTreeSet<Vec<String>>;
+
+/// The shape of a puzzle, as passed to the solver.
+#[derive(Clone, Debug)]
+struct Shape {
+    word_lens: Vec<usize>,
+    word_count: Option<usize>,
+    letters: usize,
+}
+
+impl Shape {
+    fn new(word_lens: &[usize], word_count: Option<usize>, letters: usize) -> Self {
+        Shape {
+            word_lens: word_lens.to_vec(),
+            word_count,
+            letters,
+        }
+    }
+}
+
+/// Every set of letter-disjoint words covering exactly `shape.letters` letters, found by trying
+/// every combination of the words with distinct letters and an allowed length.
+fn reference(words: &[String], shape: &Shape) -> Solutions {
+    let words: Vec<&String> = words
+        .iter()
+        .filter(|word| shape.word_lens.contains(&word.chars().count()))
+        .filter(|word| word.chars().collect::<HashSet<_>>().len() == word.chars().count())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    fn extend(
+        words: &[&String],
+        shape: &Shape,
+        chosen: &mut Vec<String>,
+        letters: &HashSet<char>,
+        solutions: &mut Solutions,
+    ) {
+        if letters.len() == shape.letters {
+            if shape.word_count.is_none_or(|count| count == chosen.len()) {
+                let mut solution = chosen.clone();
+                solution.sort();
+                solutions.insert(solution);
+            }
+            return;
+        }
+        for (i, word) in words.iter().enumerate() {
+            if word.chars().all(|c| !letters.contains(&c)) {
+                let mut letters = letters.clone();
+                letters.extend(word.chars());
+                chosen.push(word.to_string());
+                extend(&words[i + 1..], shape, chosen, &letters, solutions);
+                chosen.pop();
+            }
+        }
+    }
+
+    let mut solutions = Solutions::new();
+    extend(
+        &words,
+        shape,
+        &mut Vec::new(),
+        &HashSet::new(),
+        &mut solutions,
+    );
+    solutions
+}
+
+/// The solutions of the solver, each with its words sorted. Fails on repeated solutions.
+fn optimised(alphabet: &str, words: &[String], shape: &Shape) -> Solutions {
+    let mut builder = Solver::builder()
+        .alphabet(Alphabet::new(alphabet).unwrap())
+        .word_lens(shape.word_lens.iter().copied())
+        .letters(shape.letters);
+    if let Some(word_count) = shape.word_count {
+        builder = builder.word_count(word_count);
+    }
+    let solver = builder.build(words).unwrap();
+    let solutions: Vec<Vec<String>> = solver
+        .solutions()
+        .map(|solution| {
+            let mut words: Vec<String> = solution.words().map(|w| w.to_string()).collect();
+            words.sort();
+            words
+        })
+        .collect();
+    let len = solutions.len();
+    let solutions: Solutions = solutions.into_iter().collect();
+    assert_eq!(len, solutions.len(), "repeated solutions");
+    solutions
+}
+
+fn assert_same(alphabet: &str, words: &[&str], shape: Shape) -> Solutions {
+    let words: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+    let expected = reference(&words, &shape);
+    let actual = optimised(alphabet, &words, &shape);
+    assert_eq!(
+        actual, expected,
+        "alphabet {alphabet:?}, {shape:?}, words {words:?}"
+    );
+    expected
+}
+
+#[test]
+fn anagrams() {
+    let words = ["ab", "ba", "cd", "dc", "ef", "fe", "ce"];
+    let solutions = assert_same("abcdef", &words, Shape::new(&[2], Some(3), 6));
+    assert_eq!(solutions.len(), 8);
+}
+
+#[test]
+fn repeated_letters() {
+    let words = ["aab", "abc", "def", "dde", "fed", "ghh", "cba"];
+    let solutions = assert_same("abcdefgh", &words, Shape::new(&[3], Some(2), 6));
+    assert_eq!(solutions.len(), 4);
+}
+
+#[test]
+fn repeated_lines() {
+    let words = ["ab", "ab", "cd", "cd", "cd"];
+    let solutions = assert_same("abcd", &words, Shape::new(&[2], Some(2), 4));
+    assert_eq!(solutions.len(), 1);
+}
+
+#[test]
+fn every_letter_skippable() {
+    // every pair of letters is a word, so any four letters can be left out
+    let alphabet = "abcdefgh";
+    let letters: Vec<char> = alphabet.chars().collect();
+    let words: Vec<String> = (0..letters.len())
+        .flat_map(|i| (i + 1..letters.len()).map(move |j| (i, j)))
+        .map(|(i, j)| [letters[i], letters[j]].iter().collect())
+        .collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let solutions = assert_same(alphabet, &words, Shape::new(&[2], Some(2), 4));
+    // choose 4 of 8 letters, then split them into 2 pairs
+    assert_eq!(solutions.len(), 70 * 3);
+}
+
+#[test]
+fn no_solutions() {
+    let words = ["abc", "cde", "efa"];
+    let solutions = assert_same("abcdef", &words, Shape::new(&[3], Some(2), 6));
+    assert!(solutions.is_empty());
+}
+
+#[test]
+fn mixed_lengths() {
+    let words = ["a", "bc", "def", "ghij", "ab", "cdef", "gh", "ij", "bcd"];
+    assert_same("abcdefghij", &words, Shape::new(&[1, 2, 3, 4], None, 10));
+    assert_same("abcdefghij", &words, Shape::new(&[1, 2, 3, 4], Some(3), 9));
+    assert_same("abcdefghij", &words, Shape::new(&[2, 4], None, 8));
+}
+
+#[test]
+fn constraint_report() {
+    // "ab" is required and its anagram "ba" excluded at the same position, and "cd" and "dc"
+    // share a position where only "dc" is excluded
+    let words = ["ab", "ba", "cd", "dc", "ef", "fe", "ce", "af", "bd"];
+    let (required, excluded) = (["ab"], ["ba", "ce", "dc"]);
+    let words: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+    let shape = Shape::new(&[2], Some(3), 6);
+    let solutions = reference(&words, &shape);
+    let with = |word: &str| {
+        let with = solutions.iter().filter(|s| s.iter().any(|w| w == word));
+        with.count() as u64
+    };
+
+    let solver = Solver::builder()
+        .alphabet(Alphabet::new("abcdef").unwrap())
+        .word_len(2)
+        .word_count(3)
+        .require(required)
+        .exclude(excluded)
+        .build(&words)
+        .unwrap();
+    let report = solver.constraint_report();
+    assert_eq!(report.unconstrained, solutions.len() as u64);
+    let remaining = solutions.iter().filter(|solution| {
+        required
+            .iter()
+            .all(|word| solution.contains(&word.to_string()))
+            && excluded
+                .iter()
+                .all(|word| !solution.contains(&word.to_string()))
+    });
+    assert_eq!(report.remaining, remaining.count() as u64);
+    assert_eq!(report.remaining, solver.solutions().count() as u64);
+    assert!(report.remaining > 0);
+    let required: Vec<(String, u64)> = required
+        .iter()
+        .map(|word| (word.to_string(), solutions.len() as u64 - with(word)))
+        .collect();
+    assert_eq!(report.required, required);
+    let excluded: Vec<(String, u64)> = excluded
+        .iter()
+        .map(|word| (word.to_string(), with(word)))
+        .collect();
+    assert_eq!(report.excluded, excluded);
+}
+
+/// A xorshift generator, so the random dictionaries are the same on every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn range(&mut self, range: std::ops::RangeInclusive<usize>) -> usize {
+        range.start() + self.below(range.end() - range.start() + 1)
+    }
+}
+
+/// Random words over the first `alphabet_len` letters, some with repeated letters, along with
+/// the alphabet.
+fn random_dictionary(rng: &mut Rng, alphabet_len: usize, max_len: usize) -> (String, Vec<String>) {
+    // a-z followed by cyrillic and greek letters, for alphabets that do not fit in a u32
+    let alphabet: String = ('a'..='z')
+        .chain('а'..='я')
+        .chain('α'..='ω')
+        .take(alphabet_len)
+        .collect();
+    let letters: Vec<char> = alphabet.chars().collect();
+    let words = (0..rng.range(5..=30))
+        .map(|_| {
+            let len = rng.range(1..=max_len);
+            (0..len)
+                .map(|_| letters[rng.below(letters.len())])
+                .collect()
+        })
+        .collect();
+    (alphabet, words)
+}
+
+fn random_shape(rng: &mut Rng, alphabet_len: usize, max_len: usize) -> Shape {
+    let word_lens = match rng.below(3) {
+        0 => vec![rng.range(1..=max_len)],
+        _ => (1..=max_len).filter(|_| rng.below(2) == 0).collect(),
+    };
+    let word_lens = match word_lens.is_empty() {
+        true => vec![max_len],
+        false => word_lens,
+    };
+    let word_count = match word_lens.len() == 1 || rng.below(2) == 0 {
+        true => Some(rng.range(1..=4)),
+        false => None,
+    };
+    let letters = match word_count {
+        Some(count) if word_lens.len() == 1 => word_lens[0] * count,
+        // at most 4 words, or the number of solutions explodes
+        _ => rng.range(1..=alphabet_len.min(4 * word_lens[0])),
+    };
+    Shape {
+        word_lens,
+        word_count,
+        letters: letters.min(alphabet_len),
+    }
+}
+
+fn random(seed: u64, alphabet_lens: &[usize]) {
+    let mut rng = Rng(seed);
+    for _ in 0..200 {
+        let alphabet_len = alphabet_lens[rng.below(alphabet_lens.len())];
+        let max_len = rng.range(1..=4);
+        let (alphabet, words) = random_dictionary(&mut rng, alphabet_len, max_len);
+        let shape = random_shape(&mut rng, alphabet_len, max_len);
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        assert_same(&alphabet, &words, shape);
+    }
+}
+
+#[test]
+fn random_small_alphabets() {
+    random(0x5eed, &[4, 6, 8, 10, 12]);
+}
+
+#[test]
+fn random_large_alphabets() {
+    // exercise the u64 and u128 bitwords as well
+    random(0xcafe, &[26, 40, 70]);
+}
+
+/// A combination of anagram classes, each with its words sorted, in canonical order.
+type Classes = Vec<Vec<String>>;
+
+/// The `limit` best combinations of at most `word_count` anagram classes without the `missing`
+/// letters, found by scoring every combination. The words must be disjoint except for one,
+/// which shares fewer letters with the others than it adds, and a combination scores the
+/// letters it covers minus the letters shared.
+fn reference_best(
+    words: &[String],
+    word_lens: &[usize],
+    word_count: usize,
+    letters: usize,
+    missing: &str,
+    limit: usize,
+) -> Vec<Classes> {
+    let mut classes: BTreeMap<BTreeSet<char>, BTreeSet<String>> = BTreeMap::new();
+    for word in words {
+        let chars: BTreeSet<char> = word.chars().collect();
+        if word_lens.contains(&word.chars().count())
+            && chars.len() == word.chars().count()
+            && !word.chars().any(|c| missing.contains(c))
+        {
+            classes.entry(chars).or_default().insert(word.clone());
+        }
+    }
+    let classes: Vec<(BTreeSet<char>, Vec<String>)> = classes
+        .into_iter()
+        .map(|(chars, words)| (chars, words.into_iter().collect()))
+        .collect();
+
+    fn score(combination: &[&(BTreeSet<char>, Vec<String>)], letters: usize) -> Option<usize> {
+        let total: usize = combination.iter().map(|(chars, _)| chars.len()).sum();
+        let union = |skip: Option<usize>| {
+            let chars = combination
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| Some(*i) != skip);
+            chars
+                .flat_map(|(_, (chars, _))| chars)
+                .collect::<HashSet<_>>()
+        };
+        let covered = union(None).len();
+        let last_word = |i: usize| {
+            let (chars, _) = combination[i];
+            let others = union(Some(i));
+            let shared = chars
+                .intersection(&others.into_iter().copied().collect())
+                .count();
+            total - chars.len() == covered - (chars.len() - shared)
+                && shared > 0
+                && chars.len() - shared > shared
+        };
+        let valid = total == covered || (0..combination.len()).any(last_word);
+        (valid && covered <= letters).then(|| 2 * covered - total)
+    }
+
+    let mut scored: Vec<(usize, Classes)> = Vec::new();
+    let mut combination = Vec::new();
+    fn extend<'c>(
+        classes: &'c [(BTreeSet<char>, Vec<String>)],
+        word_count: usize,
+        letters: usize,
+        combination: &mut Vec<&'c (BTreeSet<char>, Vec<String>)>,
+        scored: &mut Vec<(usize, Classes)>,
+    ) {
+        if let Some(score) = score(combination, letters).filter(|_| !combination.is_empty()) {
+            let mut words: Classes = combination.iter().map(|(_, words)| words.clone()).collect();
+            words.sort();
+            scored.push((score, words));
+        }
+        if combination.len() == word_count {
+            return;
+        }
+        for (i, class) in classes.iter().enumerate() {
+            combination.push(class);
+            extend(&classes[i + 1..], word_count, letters, combination, scored);
+            combination.pop();
+        }
+    }
+    extend(&classes, word_count, letters, &mut combination, &mut scored);
+    scored.sort_by(|(a_score, a), (b_score, b)| (Reverse(a_score), a).cmp(&(Reverse(b_score), b)));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, words)| words)
+        .collect()
+}
+
+#[test]
+fn random_best() {
+    let mut rng = Rng(0xbe57);
+    for _ in 0..300 {
+        let alphabet_len = rng.range(4..=12);
+        let max_len = rng.range(1..=4);
+        let (alphabet, words) = random_dictionary(&mut rng, alphabet_len, max_len);
+        let word_lens: Vec<usize> = (1..=max_len).filter(|_| rng.below(2) == 0).collect();
+        let word_lens = match word_lens.is_empty() {
+            true => vec![max_len],
+            false => word_lens,
+        };
+        let missing: String = alphabet.chars().filter(|_| rng.below(6) == 0).collect();
+        let available = alphabet_len - missing.chars().count();
+        if available == 0 {
+            continue;
+        }
+        let word_count = rng.range(1..=4);
+        let letters = rng.range(1..=available);
+        let limit = rng.range(1..=6);
+
+        let solver = Solver::builder()
+            .alphabet(Alphabet::new(&alphabet).unwrap())
+            .word_lens(word_lens.iter().copied())
+            .word_count(word_count)
+            .letters(letters)
+            .missing(&missing)
+            .expand_anagrams(false)
+            .build(&words)
+            .unwrap();
+        let sink = VecSink::new();
+        solver.run_best(limit, &sink);
+        let actual: Vec<Classes> = sink
+            .into_inner()
+            .iter()
+            .map(|solution| {
+                let classes = solution.anagrams().iter();
+                let classes = classes.map(|words| words.iter().map(|w| w.to_string()).collect());
+                classes.collect()
+            })
+            .collect();
+        let expected = reference_best(&words, &word_lens, word_count, letters, &missing, limit);
+        assert_eq!(
+            actual, expected,
+            "alphabet {alphabet:?}, word lengths {word_lens:?}, {word_count} words, {letters} \
+             letters, missing {missing:?}, limit {limit}, words {words:?}"
+        );
+    }
+}
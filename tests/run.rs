@@ -0,0 +1,363 @@
+//! Checks that a limited or cancelled search ends early and reports why, that progress is
+//! reported for every root, that a ranked run emits the best solutions first, that the shards
+//! of a run add up to the whole run, as do their constraint reports, that a run resumed from a
+//! checkpoint finds every solution, and that verifying a solution file reports what is wrong
+//! with it.
+
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use five_five::{
+    Alphabet, CancellationToken, Checkpoint, Frequencies, Progress, Score, Shard, Solver,
+    SolverBuilder, StopReason, VecSink,
+};
+
+/// Every pair of letters of a-h is a word, so there are 105 ways to cover all of them with four
+/// words.
+fn builder() -> (SolverBuilder, Vec<String>) {
+    let letters: Vec<char> = ('a'..='h').collect();
+    let words = (0..letters.len())
+        .flat_map(|i| (i + 1..letters.len()).map(move |j| (i, j)))
+        .map(|(i, j)| [letters[i], letters[j]].iter().collect())
+        .collect();
+    let builder = Solver::builder()
+        .alphabet(Alphabet::new("abcdefgh").unwrap())
+        .word_len(2)
+        .word_count(4);
+    (builder, words)
+}
+
+fn run(builder: SolverBuilder, words: &[String]) -> (usize, Option<StopReason>) {
+    let solver = builder.build(words).unwrap();
+    let sink = VecSink::new();
+    let report = solver.run(&sink);
+    let solutions = sink.into_inner().len();
+    assert_eq!(solutions as u64, report.solutions);
+    (solutions, report.stopped)
+}
+
+#[test]
+fn unlimited() {
+    let (builder, words) = builder();
+    assert_eq!(run(builder, &words), (105, None));
+}
+
+#[test]
+fn limit() {
+    for limit in [0, 1, 10, 104] {
+        let (builder, words) = builder();
+        let solutions = run(builder.limit(limit), &words);
+        assert_eq!(solutions, (limit as usize, Some(StopReason::Limit)));
+    }
+    let (builder, words) = builder();
+    assert_eq!(run(builder.limit(1000), &words), (105, None));
+}
+
+#[test]
+fn limit_sorted() {
+    let (builder, words) = builder();
+    let solutions = run(builder.limit(10).sorted(true), &words);
+    assert_eq!(solutions, (10, Some(StopReason::Limit)));
+}
+
+#[test]
+fn cancelled() {
+    let (builder, words) = builder();
+    let token = CancellationToken::new();
+    token.cancel();
+    let solutions = run(builder.cancellation(token), &words);
+    assert_eq!(solutions, (0, Some(StopReason::Cancelled)));
+}
+
+#[test]
+fn progress() {
+    let (builder, words) = builder();
+    let seen: Arc<Mutex<Vec<Progress>>> = Arc::default();
+    let progress = Arc::clone(&seen);
+    let solver = builder
+        .progress(move |p| progress.lock().unwrap().push(*p))
+        .build(&words)
+        .unwrap();
+    let report = solver.run(&VecSink::new());
+    let mut seen = seen.lock().unwrap().clone();
+    assert_eq!(seen.len(), report.root_count);
+    seen.sort_by_key(|p| p.roots_done);
+    let last = seen.last().unwrap();
+    assert_eq!(
+        (last.roots_done, last.roots),
+        (report.root_count, report.root_count)
+    );
+    assert!(seen.iter().all(|p| p.solutions <= 105));
+    assert_eq!(seen.iter().map(|p| p.solutions).max(), Some(105));
+}
+
+#[test]
+fn ranked() {
+    // "cd" and "CD" are the same word once normalised, so "ab cd ef gh" scores 9 and beats
+    // "ab ce df gh", which scores 7; every other solution has a word without a frequency
+    let frequencies = ["ab 10", "cd 5", "CD 4", "ef 20", "gh 20", "ce 7", "df 7"];
+    let (builder, words) = builder();
+    let builder = builder.frequencies(Frequencies::parse(frequencies).unwrap());
+    let canonical = |solver: &Solver| -> Vec<String> {
+        let sink = VecSink::new();
+        let report = solver.run(&sink);
+        let solutions = sink.into_inner();
+        assert_eq!(solutions.len() as u64, report.solutions);
+        solutions
+            .into_iter()
+            .map(|mut solution| {
+                solution.canonicalize();
+                solution.to_string()
+            })
+            .collect()
+    };
+    let ranked = builder
+        .clone()
+        .rank(Score::Min)
+        .top(3)
+        .build(&words)
+        .unwrap();
+    let ranked = canonical(&ranked);
+    assert_eq!(ranked[..2], ["ab cd ef gh", "ab ce df gh"]);
+    // ties in canonical order
+    let sorted = canonical(&builder.clone().sorted(true).build(&words).unwrap());
+    let first_tied = sorted
+        .iter()
+        .find(|solution| !ranked[..2].contains(solution));
+    assert_eq!(ranked.get(2), first_tied);
+    assert_eq!(ranked.len(), 3);
+
+    let all = builder.rank(Score::LogSum).build(&words).unwrap();
+    assert_eq!(canonical(&all)[..2], ranked[..2]);
+    assert_eq!(canonical(&all).len(), 105);
+}
+
+#[test]
+fn shards() {
+    let (builder, words) = builder();
+    let solver = builder.clone().build(&words).unwrap();
+    let whole: BTreeSet<String> = solver.solutions().map(|s| s.to_string()).collect();
+    for count in 1..=4 {
+        let mut union = BTreeSet::new();
+        for index in 1..=count {
+            let shard = Shard::new(index, count).unwrap();
+            let solver = builder.clone().shard(shard).build(&words).unwrap();
+            assert_eq!(
+                solver.fingerprint(),
+                builder.clone().build(&words).unwrap().fingerprint()
+            );
+            for solution in solver.solutions() {
+                assert!(
+                    union.insert(solution.to_string()),
+                    "{solution} in two shards"
+                );
+            }
+        }
+        assert_eq!(union, whole);
+    }
+    assert!(Shard::new(0, 2).is_err());
+    assert!("3/2".parse::<Shard>().is_err());
+    // the same on every machine, as shards run elsewhere are compared by fingerprint
+    assert_eq!(solver.fingerprint(), 0xb87c_029a_47e7_3edf);
+}
+
+#[test]
+fn constraint_report() {
+    let (builder, words) = builder();
+    let builder = builder.exclude(["ab"]);
+    let report = builder.clone().build(&words).unwrap().constraint_report();
+    assert_eq!((report.unconstrained, report.remaining), (105, 90));
+    assert_eq!(report.stopped, None);
+
+    // the counts of the shards add up to those of the whole run
+    let (mut unconstrained, mut remaining) = (0, 0);
+    for index in 1..=3 {
+        let shard = Shard::new(index, 3).unwrap();
+        let solver = builder.clone().shard(shard).build(&words).unwrap();
+        let report = solver.constraint_report();
+        assert_eq!(report.shard, Some(shard));
+        unconstrained += report.unconstrained;
+        remaining += report.remaining;
+    }
+    assert_eq!((unconstrained, remaining), (105, 90));
+
+    let report = builder
+        .clone()
+        .limit(1)
+        .build(&words)
+        .unwrap()
+        .constraint_report();
+    assert_eq!(report.unconstrained, 1);
+    assert_eq!(report.stopped, Some(StopReason::Limit));
+    assert!(report.to_string().contains("(partial, "));
+    let token = CancellationToken::new();
+    token.cancel();
+    let solver = builder.cancellation(token).build(&words).unwrap();
+    let report = solver.constraint_report();
+    assert_eq!(report.unconstrained, 0);
+    assert_eq!(report.stopped, Some(StopReason::Cancelled));
+}
+
+#[test]
+fn parse_solutions() {
+    let (builder, words) = builder();
+    let solver = builder.build(&words).unwrap();
+    let mut solutions: Vec<String> = solver.solutions().map(|s| s.to_string()).collect();
+    let parsed = solver.parse_solutions(&solutions).unwrap();
+    assert_eq!(parsed.len(), 105);
+    assert!(solver.parse_solutions(["ab cd ef"]).is_err());
+    solutions.push("ab cd ef gh".to_owned());
+    assert_eq!(solver.parse_solutions(&solutions).unwrap().len(), 106);
+}
+
+#[test]
+fn verify() {
+    let (builder, words) = builder();
+    let solver = builder.clone().build(&words).unwrap();
+    let mut rows: Vec<String> = solver
+        .solutions()
+        .map(|mut solution| {
+            solution.canonicalize();
+            solution.to_string()
+        })
+        .collect();
+    rows.sort();
+    assert!(solver.verify(&rows).is_ok());
+
+    let missing = rows.remove(0);
+    let mut reordered: Vec<&str> = rows[0].split(' ').collect();
+    reordered.reverse();
+    rows.push(reordered.join(" "));
+    rows.extend(["ab cd ef gz", "ab ac de fg", "", "ab,cd,ef,gh"].map(String::from));
+    let verification = solver.verify(&rows);
+    assert_eq!((verification.rows, verification.valid), (108, 105));
+    let invalid: Vec<usize> = verification.invalid.iter().map(|(line, _)| *line).collect();
+    assert_eq!(invalid, [106, 107, 109]);
+    assert_eq!(verification.duplicates, [(105, 1)]);
+    assert_eq!(verification.missing, [missing]);
+    assert!(!verification.is_ok());
+
+    // rows breaking the letter and word constraints
+    let missing_h = builder.clone().missing("h").letters(6).word_count(3);
+    let verification = missing_h.build(&words).unwrap().verify(["ab cd eh"]);
+    assert_eq!(
+        verification.invalid,
+        [(1, "eh uses unavailable letters".to_owned())]
+    );
+    let required = builder.require(["ab"]).build(&words).unwrap();
+    let verification = required.verify(["ac bd ef gh"]);
+    let reason = "does not have the required word ab".to_owned();
+    assert_eq!(verification.invalid, [(1, reason)]);
+}
+
+/// The solutions of a checkpointed run, each with its words sorted.
+fn checkpointed(
+    solver: &Solver,
+    resume: Option<&Checkpoint>,
+    log: &mut Vec<u8>,
+) -> BTreeSet<String> {
+    let sink = VecSink::new();
+    solver.run_checkpointed(&sink, resume, log).unwrap();
+    let solutions = sink.into_inner().into_iter().map(|solution| {
+        let mut words: Vec<&str> = solution.words().map(|w| w.as_str()).collect();
+        words.sort();
+        words.join(" ")
+    });
+    solutions.collect()
+}
+
+#[test]
+fn checkpoint() {
+    let (builder, words) = builder();
+    let solver = builder.clone().build(&words).unwrap();
+    let mut log = Vec::new();
+    let whole = checkpointed(&solver, None, &mut log);
+    assert_eq!(whole.len(), 105);
+    let log = String::from_utf8(log).unwrap();
+    let checkpoint = Checkpoint::parse(log.lines()).unwrap();
+    assert_eq!(
+        checkpoint.roots_done(),
+        solver.run(&VecSink::new()).root_count
+    );
+
+    // cut the log off in the middle of a line after the third root
+    let third = log.match_indices("# done").nth(2).unwrap().0;
+    let mut log = log.as_bytes()[..third + 20].to_vec();
+    let checkpoint = Checkpoint::parse(log.split(|b| *b == b'\n')).unwrap();
+    assert_eq!(checkpoint.roots_done(), 3);
+
+    // the solutions of the roots done count against the limit and in the progress
+    let limited = builder.clone().limit(1).build(&words).unwrap();
+    let sink = VecSink::new();
+    let report = limited
+        .run_checkpointed(&sink, Some(&checkpoint), Vec::new())
+        .unwrap();
+    assert_eq!(sink.into_inner().len(), 1);
+    assert_eq!(report.stopped, Some(StopReason::Limit));
+    let solutions = Arc::new(AtomicU64::new(0));
+    let progress = Arc::clone(&solutions);
+    let tracked = builder
+        .clone()
+        .progress(move |p| {
+            progress.fetch_max(p.solutions, Ordering::Relaxed);
+        })
+        .build(&words)
+        .unwrap();
+    let report = tracked
+        .run_checkpointed(&VecSink::new(), Some(&checkpoint), Vec::new())
+        .unwrap();
+    assert_eq!(report.solutions, 105);
+    assert_eq!(solutions.load(Ordering::Relaxed), 105);
+
+    assert_eq!(checkpointed(&solver, Some(&checkpoint), &mut log), whole);
+    let checkpoint = Checkpoint::parse(log.split(|b| *b == b'\n')).unwrap();
+    assert_eq!(
+        checkpoint.roots_done(),
+        solver.run(&VecSink::new()).root_count
+    );
+
+    // cut the log off in the middle of the marker ending the fourth root
+    let log = String::from_utf8(log).unwrap();
+    let fourth = log.match_indices("# done").nth(3).unwrap().0;
+    let mut log = log.as_bytes()[..fourth + 4].to_vec();
+    assert!(log.ends_with(b"# do"));
+    let checkpoint = Checkpoint::parse(log.split(|b| *b == b'\n')).unwrap();
+    assert_eq!(checkpoint.roots_done(), 3);
+    assert_eq!(checkpointed(&solver, Some(&checkpoint), &mut log), whole);
+    let checkpoint = Checkpoint::parse(log.split(|b| *b == b'\n')).unwrap();
+    assert_eq!(
+        checkpoint.roots_done(),
+        solver.run(&VecSink::new()).root_count
+    );
+
+    // a different puzzle over the same dictionary
+    let solver = builder.word_count(3).letters(6).build(&words).unwrap();
+    let sink = VecSink::new();
+    assert!(solver
+        .run_checkpointed(&sink, Some(&checkpoint), Vec::new())
+        .is_err());
+}
+
+#[test]
+fn checkpoint_required() {
+    // the required word and another position have anagrams
+    let (builder, mut words) = builder();
+    words.extend(["ba".to_owned(), "dc".to_owned()]);
+    let solver = builder.require(["ab"]).build(&words).unwrap();
+    let mut log = Vec::new();
+    let whole = checkpointed(&solver, None, &mut log);
+    assert_eq!(whole.len(), 18);
+    assert!(whole.iter().all(|solution| solution.starts_with("ab ")));
+
+    let log = String::from_utf8(log).unwrap();
+    let last = log.rfind("# done").unwrap();
+    let mut log = log.as_bytes()[..last].to_vec();
+    let checkpoint = Checkpoint::parse(log.split(|b| *b == b'\n')).unwrap();
+    assert!(checkpoint.roots_done() > 0);
+    assert_eq!(checkpointed(&solver, Some(&checkpoint), &mut log), whole);
+}